@@ -1,14 +1,16 @@
 mod latest;
+mod retime;
 mod sql;
 
 pub use self::latest::do_json_latest_command;
+pub use self::retime::do_json_retime_command;
 pub use self::sql::do_json_sql_command;
 
 use json::JsonValue;
 
 // https://github.com/ms705/nom-sql
 
-fn find_field<'a>(field: &Vec<String>, json_input: &'a JsonValue) -> Option<&'a JsonValue> {
+fn find_field<'a>(field: &[String], json_input: &'a JsonValue) -> Option<&'a JsonValue> {
     let mut value = json_input;
 
     for part in field {
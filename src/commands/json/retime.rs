@@ -0,0 +1,277 @@
+use std::io::{BufRead, BufReader};
+
+use chrono::prelude::*;
+use chrono_tz::Tz;
+use clap::ArgMatches;
+use json::JsonValue;
+use toolkit::time::{Calendar, DateRule, ParseOptions};
+
+use super::{find_field, parse_path};
+use crate::commands::time::{
+    counts_business_days, epoch_nanos, load_holidays, parse_base, parse_first, parse_options,
+    parse_zone, preferred_rule, ValueFormat,
+};
+use crate::commands::CliError;
+
+/// How the values are read as times: the time relative ones count from, the
+/// date order to take when they're ambiguous, and the `--unit` and `--as`.
+struct Reading<'a> {
+    base: DateTime<FixedOffset>,
+    prefer: Option<DateRule>,
+    options: ParseOptions<'a>,
+}
+
+pub fn do_json_retime_command(args: &ArgMatches) -> Result<(), CliError> {
+    let paths: Vec<Vec<String>> = args.values_of("path").unwrap().map(parse_path).collect();
+
+    let format = match ValueFormat::from_name(args.value_of("to").unwrap_or("iso")) {
+        Some(format) => format,
+        None => {
+            error!("Unable to format to {}", args.value_of("to").unwrap());
+            return Err(CliError::new("Format error", 2));
+        }
+    };
+
+    let zone = match args.value_of("zone") {
+        Some(zone) => Some(parse_zone(zone)?),
+        None => None,
+    };
+
+    let base = parse_base(args)?;
+    let prefer = preferred_rule(args);
+    let weekends = Calendar::default();
+    // Read the first time a value counts business days, then kept for the rest.
+    let mut holidays = None;
+
+    let input_paths: Vec<&str> = args.values_of("INPUT").unwrap().collect();
+    for input_path in input_paths {
+        let reader = match crate::commands::file::open_file(input_path) {
+            Ok(reader) => BufReader::new(reader),
+            Err(e) => {
+                error!("Unable to open {} because {}", input_path, e);
+                return Err(CliError::new("Unable to open file", 1));
+            }
+        };
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    error!("IO error: Line {}: {}", index + 1, err);
+                    return Err(CliError::new("IO error", 1));
+                }
+            };
+
+            let mut json_line = match json::parse(&line) {
+                Ok(json_line) => json_line,
+                Err(_) => {
+                    warn!("Line {}: passing through, it isn't JSON", index + 1);
+                    println!("{}", line);
+                    continue;
+                }
+            };
+
+            for path in &paths {
+                if holidays.is_none() && field_counts_business_days(path, &json_line) {
+                    holidays = Some(load_holidays(args)?);
+                }
+                let reading = Reading {
+                    base,
+                    prefer,
+                    options: parse_options(args, holidays.as_ref().unwrap_or(&weekends)),
+                };
+                if let Err(reason) = retime_field(path, &mut json_line, &reading, format, zone) {
+                    warn!("Line {}: {} at .{}", index + 1, reason, path.join("."));
+                }
+            }
+
+            println!("{}", json_line.dump());
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the value at `path` counts business days, like `+3 business days`.
+fn field_counts_business_days(path: &[String], json_input: &JsonValue) -> bool {
+    match find_field(path, json_input) {
+        Some(value) if value.is_string() => counts_business_days(&value.to_string()),
+        _ => false,
+    }
+}
+
+/// Rewrite the time at `path`, if there is one. Returns why the field was left
+/// alone when it can't be read as a time or written in `format`.
+fn retime_field(
+    path: &[String],
+    json_input: &mut JsonValue,
+    reading: &Reading,
+    format: ValueFormat,
+    zone: Option<Tz>,
+) -> Result<(), String> {
+    let original = match find_field(path, json_input) {
+        Some(value) if value.is_number() || value.is_string() => value.to_string(),
+        _ => return Ok(()),
+    };
+
+    let datetime = match parse_first(&original, &reading.base, reading.prefer, reading.options) {
+        Some(datetime) => datetime,
+        None => return Err(format!("unable to understand `{}`", original)),
+    };
+
+    let datetime = match zone {
+        Some(zone) => {
+            let in_zone = datetime.with_timezone(&zone);
+            in_zone.with_timezone(&in_zone.offset().fix())
+        }
+        None => datetime,
+    };
+
+    let value = match format {
+        ValueFormat::Epoch => JsonValue::from(datetime.timestamp()),
+        ValueFormat::EpochMillis => JsonValue::from(datetime.timestamp_millis()),
+        ValueFormat::EpochNanos => match epoch_nanos(&datetime) {
+            Some(nanos) => JsonValue::from(nanos),
+            None => return Err(format!("`{}` is out of range for epoch-ns", original)),
+        },
+        _ => JsonValue::from(format.render(&datetime)),
+    };
+
+    let mut target = json_input;
+    for part in path {
+        target = &mut target[part.as_str()];
+    }
+    *target = value;
+
+    Ok(())
+}
+
+#[cfg(test)]
+fn test_reading(prefer: Option<DateRule>, options: ParseOptions) -> Reading {
+    Reading {
+        base: FixedOffset::east(0).ymd(2019, 6, 1).and_hms(0, 0, 0),
+        prefer,
+        options,
+    }
+}
+
+#[test]
+fn retime_rewrites_found_fields() {
+    let reading = test_reading(None, ParseOptions::default());
+    let mut record = json::parse(
+        r#"{"created_at": 1543897222205, "meta": {"seen": "2018-12-03 20:20:22 -08:00"}, "id": "abc"}"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        Ok(()),
+        retime_field(
+            &parse_path(".created_at"),
+            &mut record,
+            &reading,
+            ValueFormat::Rfc3339,
+            Some(chrono_tz::UTC)
+        )
+    );
+    assert_eq!(
+        Ok(()),
+        retime_field(
+            &parse_path(".meta.seen"),
+            &mut record,
+            &reading,
+            ValueFormat::Epoch,
+            None
+        )
+    );
+    assert_eq!(
+        Ok(()),
+        retime_field(
+            &parse_path(".missing"),
+            &mut record,
+            &reading,
+            ValueFormat::Epoch,
+            None
+        )
+    );
+    assert_eq!(
+        Err(s!("unable to understand `abc`")),
+        retime_field(
+            &parse_path(".id"),
+            &mut record,
+            &reading,
+            ValueFormat::Epoch,
+            None
+        )
+    );
+
+    assert_eq!("2018-12-04T04:20:22.205+00:00", record["created_at"]);
+    assert_eq!(1_543_897_222, record["meta"]["seen"]);
+    assert!(record["missing"].is_null());
+}
+
+#[test]
+fn retime_leaves_fields_it_cannot_write() {
+    let reading = test_reading(None, ParseOptions::default());
+    let mut record =
+        json::parse(r#"{"born": "1642-12-25T00:00:00Z", "day": "2018-12-04"}"#).unwrap();
+
+    assert_eq!(
+        Err(s!("`1642-12-25T00:00:00Z` is out of range for epoch-ns")),
+        retime_field(
+            &parse_path(".born"),
+            &mut record,
+            &reading,
+            ValueFormat::EpochNanos,
+            None
+        )
+    );
+    assert_eq!(
+        Ok(()),
+        retime_field(
+            &parse_path(".day"),
+            &mut record,
+            &reading,
+            ValueFormat::Rfc3339,
+            None
+        )
+    );
+
+    assert_eq!("1642-12-25T00:00:00Z", record["born"]);
+    assert!(record["day"]
+        .as_str()
+        .unwrap()
+        .starts_with("2018-12-04T00:00:00"));
+}
+
+#[test]
+fn retime_follows_the_reading_options() {
+    use toolkit::time::EpochUnit;
+
+    let mut record =
+        json::parse(r#"{"day": "04/12/2018 10:00 +0000", "at": 1543897222, "ago": "3 days ago"}"#)
+            .unwrap();
+
+    let reading = test_reading(
+        Some(DateRule::DayMonthYear),
+        ParseOptions {
+            unit: Some(EpochUnit::Milliseconds),
+            ..ParseOptions::default()
+        },
+    );
+    for path in &[".day", ".at", ".ago"] {
+        assert_eq!(
+            Ok(()),
+            retime_field(
+                &parse_path(path),
+                &mut record,
+                &reading,
+                ValueFormat::Rfc3339,
+                None
+            )
+        );
+    }
+
+    assert_eq!("2018-12-04T10:00:00+00:00", record["day"]);
+    assert_eq!("1970-01-18T20:51:37.222+00:00", record["at"]);
+    assert_eq!("2019-05-29T00:00:00+00:00", record["ago"]);
+}
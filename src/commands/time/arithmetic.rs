@@ -0,0 +1,104 @@
+use clap::ArgMatches;
+
+use chrono::prelude::*;
+use chrono_tz::Tz;
+use toolkit::time::relative::{self, Shift};
+use toolkit::time::{Calendar, Permutation};
+
+use crate::commands::CliError;
+
+pub fn do_time_add_command(args: &ArgMatches) -> Result<(), CliError> {
+    do_arithmetic(args, false)
+}
+
+pub fn do_time_sub_command(args: &ArgMatches) -> Result<(), CliError> {
+    do_arithmetic(args, true)
+}
+
+fn do_arithmetic(args: &ArgMatches, subtract: bool) -> Result<(), CliError> {
+    let base = super::parse_base(args)?;
+    let input = args.value_of("INPUT").unwrap();
+
+    let durations: Vec<&str> = args.values_of("DURATION").unwrap().collect();
+    let durations = durations.join(" ");
+    let mut shifts = match relative::parse_shifts(&durations) {
+        Some(shifts) => shifts,
+        None => {
+            error!("Unable to understand duration `{}`", durations);
+            return Err(CliError::new("Unknown duration", 1));
+        }
+    };
+
+    if subtract {
+        shifts = shifts.iter().map(|x| x.negate()).collect();
+    }
+
+    let zone = match args.value_of("in_zone") {
+        Some(zone) => Some(super::parse_zone(zone)?),
+        None => None,
+    };
+    let holidays = super::business::holidays_if(
+        super::business::counts_business_days(input)
+            || super::business::steps_business_days(&shifts),
+        args,
+    )?;
+    let permutations = super::parse_permutations(input, &base, &holidays, args)?;
+
+    let mut results = Vec::new();
+    for permutation in permutations {
+        let datetime = permutation.datetime;
+        match shift(datetime, zone.or(permutation.zone), &shifts, &holidays) {
+            Some(shifted) => results.push(Permutation {
+                datetime: shifted,
+                ..permutation
+            }),
+            None => {
+                error!("Adding durations to {} is out of range", datetime);
+                return Err(CliError::new("Out of range", 1));
+            }
+        }
+    }
+
+    super::render_results(&results, args)
+}
+
+/// Apply `shifts` in `zone`, so days and months follow its clock changes.
+/// Without one, a time on the local clock follows the local zone, and any
+/// other keeps the offset it was written in.
+fn shift(
+    datetime: DateTime<FixedOffset>,
+    zone: Option<Tz>,
+    shifts: &[Shift],
+    holidays: &Calendar,
+) -> Option<DateTime<FixedOffset>> {
+    match zone {
+        Some(zone) => relative::apply_shifts(datetime.with_timezone(&zone), shifts, holidays)
+            .map(|x| x.with_timezone(&x.offset().fix())),
+        None if super::is_local(&datetime) => {
+            relative::apply_shifts(datetime.with_timezone(&Local), shifts, holidays)
+                .map(|x| x.with_timezone(&x.offset().fix()))
+        }
+        None => relative::apply_shifts(datetime, shifts, holidays),
+    }
+}
+
+#[test]
+fn shift_keeps_the_offset_it_was_given() {
+    let datetime = DateTime::parse_from_rfc3339("2018-12-04T10:00:00+09:00").unwrap();
+    let shifts = relative::parse_shifts("1h").unwrap();
+
+    assert_eq!(
+        Some(s!("2018-12-04T11:00:00+09:00")),
+        shift(datetime, None, &shifts, &Calendar::default()).map(|x| x.to_rfc3339())
+    );
+    assert_eq!(
+        Some(s!("2018-12-04T02:00:00+00:00")),
+        shift(
+            datetime,
+            Some(chrono_tz::UTC),
+            &shifts,
+            &Calendar::default()
+        )
+        .map(|x| x.to_rfc3339())
+    );
+}
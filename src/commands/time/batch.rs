@@ -0,0 +1,158 @@
+use std::io::{BufRead, BufReader};
+
+use chrono::prelude::*;
+use clap::ArgMatches;
+use toolkit::time::{self, Calendar, DateRule, ParseOptions};
+
+use super::output::ValueFormat;
+use crate::commands::CliError;
+
+/// Convert every line of `--batch`, writing one value per line. Lines that
+/// can't be read are reported with their line number and left blank in the
+/// output, so the output still lines up with the input.
+pub fn do_time_batch(args: &ArgMatches, path: &str) -> Result<(), CliError> {
+    let base = super::parse_base(args)?;
+    let prefer = super::preferred_rule(args);
+    let format = match ValueFormat::from_name(args.value_of("batch_format").unwrap_or("rfc3339")) {
+        Some(format) => format,
+        None => {
+            error!(
+                "Unable to format to {}",
+                args.value_of("batch_format").unwrap()
+            );
+            return Err(CliError::new("Format error", 2));
+        }
+    };
+
+    let reader = match crate::commands::file::open_file(path) {
+        Ok(reader) => BufReader::new(reader),
+        Err(e) => {
+            error!("Unable to open {} because {}", path, e);
+            return Err(CliError::new("Unable to open file", 1));
+        }
+    };
+
+    // Read the first time a line counts business days, then kept for the rest.
+    let mut holidays = None;
+    let mut failures = 0;
+    for (index, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                error!("IO error: Line {}: {}", index + 1, err);
+                return Err(CliError::new("IO error", 1));
+            }
+        };
+
+        if line.trim().is_empty() {
+            println!();
+            continue;
+        }
+
+        if holidays.is_none() && super::business::counts_business_days(&line) {
+            holidays = Some(super::business::load_holidays(args)?);
+        }
+        let weekends = Calendar::default();
+        let options = super::parse_options(args, holidays.as_ref().unwrap_or(&weekends));
+        match convert_line(&line, &base, prefer, options, format) {
+            Ok(converted) => println!("{}", converted),
+            Err(reason) => {
+                failures += 1;
+                error!(
+                    "Line {}: unable to understand `{}`: {}",
+                    index + 1,
+                    line.trim(),
+                    reason
+                );
+                println!();
+            }
+        }
+    }
+
+    if failures != 0 {
+        error!("{} line(s) could not be converted", failures);
+        return Err(CliError::new("Unknown format", 1));
+    }
+
+    Ok(())
+}
+
+fn convert_line(
+    line: &str,
+    base: &DateTime<FixedOffset>,
+    prefer: Option<DateRule>,
+    options: ParseOptions,
+    format: ValueFormat,
+) -> Result<String, String> {
+    let parsed = time::parse_with_options(line.trim(), base, options).map_err(|x| x.to_string())?;
+
+    match super::choose_one(parsed, prefer) {
+        Some(permutation) => Ok(format.render(&permutation.datetime)),
+        None => Err(s!("no reading of it is a time that exists")),
+    }
+}
+
+#[test]
+fn convert_batch_lines() {
+    let base = FixedOffset::east(0).ymd(2019, 6, 1).and_hms(0, 0, 0);
+
+    assert_eq!(
+        Ok(s!("2018-12-04T04:20:22+00:00")),
+        convert_line(
+            "1543897222",
+            &base,
+            None,
+            ParseOptions::default(),
+            ValueFormat::Rfc3339
+        )
+    );
+    assert_eq!(
+        Ok(s!("1543897222000")),
+        convert_line(
+            " 2018-12-03 20:20:22 -08:00 ",
+            &base,
+            None,
+            ParseOptions::default(),
+            ValueFormat::EpochMillis
+        )
+    );
+    assert_eq!(
+        Ok(s!("2018-12-04T04:20:00+00:00")),
+        convert_line(
+            "04/12/2018 04:20 +0000",
+            &base,
+            Some(DateRule::DayMonthYear),
+            ParseOptions::default(),
+            ValueFormat::Utc
+        )
+    );
+    assert!(convert_line(
+        "not a time",
+        &base,
+        None,
+        ParseOptions::default(),
+        ValueFormat::Epoch
+    )
+    .is_err());
+}
+
+#[test]
+fn bad_lines_fail_alone() {
+    let base = FixedOffset::east(0).ymd(2019, 6, 1).and_hms(0, 0, 0);
+    let lines = [
+        "1543897222",
+        "25:00",
+        "2018-12-04 12:30 pm +0000",
+        "12:99 pm",
+    ];
+
+    let converted: Vec<Result<String, String>> = lines
+        .iter()
+        .map(|x| convert_line(x, &base, None, ParseOptions::default(), ValueFormat::Epoch))
+        .collect();
+
+    assert_eq!(Ok(s!("1543897222")), converted[0]);
+    assert_eq!(Err(s!("Invalid time 25:00")), converted[1]);
+    assert_eq!(Ok(s!("1543926600")), converted[2]);
+    assert!(converted[3].is_err());
+}
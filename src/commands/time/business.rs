@@ -0,0 +1,121 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use clap::ArgMatches;
+use colored::*;
+use toolkit::time::relative::{self, Shift, TimeUnit};
+use toolkit::time::Calendar;
+
+use crate::commands::CliError;
+
+pub const HOLIDAYS_ENV: &str = "TOOLKIT_HOLIDAYS";
+
+/// Whether `input` counts business days, like `+3 business days`.
+pub fn counts_business_days(input: &str) -> bool {
+    match relative::extract(input) {
+        Some((relative, _)) => steps_business_days(&relative.shifts),
+        None => false,
+    }
+}
+
+/// Whether stepping by `shifts` counts business days.
+pub fn steps_business_days(shifts: &[Shift]) -> bool {
+    shifts.iter().any(|x| x.unit == TimeUnit::BusinessDay)
+}
+
+/// The holidays, read only when they're `needed` so a broken holidays file
+/// doesn't get in the way of everything else.
+pub fn holidays_if(needed: bool, args: &ArgMatches) -> Result<Calendar, CliError> {
+    if needed {
+        load_holidays(args)
+    } else {
+        Ok(Calendar::default())
+    }
+}
+
+/// The holidays `input` needs, only read when it counts business days.
+pub fn holidays_for(input: &str, args: &ArgMatches) -> Result<Calendar, CliError> {
+    holidays_if(counts_business_days(input), args)
+}
+
+/// The holidays business days skip. Read from every `--holidays` file, then
+/// the files in the `TOOLKIT_HOLIDAYS` environment variable (comma
+/// separated), then the `toolkit/holidays` file in the user's config
+/// directory. Without any, only weekends are skipped.
+pub fn load_holidays(args: &ArgMatches) -> Result<Calendar, CliError> {
+    let paths: Vec<PathBuf> = if let Some(paths) = args.values_of("holidays") {
+        paths.map(PathBuf::from).collect()
+    } else if let Ok(paths) = env::var(HOLIDAYS_ENV) {
+        paths
+            .split(',')
+            .filter(|x| !x.trim().is_empty())
+            .map(|x| PathBuf::from(x.trim()))
+            .collect()
+    } else {
+        super::zones::config_path("holidays")
+            .into_iter()
+            .filter(|x| x.exists())
+            .collect()
+    };
+
+    let mut calendar = Calendar::default();
+    for path in paths {
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                error!("Unable to open {} because {}", path.display(), e);
+                return Err(CliError::new("Unable to open file", 1));
+            }
+        };
+
+        match Calendar::parse(&contents) {
+            Ok(parsed) => calendar.extend(parsed),
+            Err(reason) => {
+                error!(
+                    "Unable to read holidays from {}: {}",
+                    path.display(),
+                    reason
+                );
+                return Err(CliError::new("Format error", 2));
+            }
+        }
+    }
+
+    Ok(calendar)
+}
+
+pub fn do_time_is_business_day_command(args: &ArgMatches) -> Result<(), CliError> {
+    let base = super::parse_base(args)?;
+    let calendar = load_holidays(args)?;
+
+    let date = match args.values_of("DATE") {
+        Some(date) => {
+            let input: Vec<&str> = date.collect();
+            super::parse_one(&input.join(" "), &base, &calendar, args)?
+                .datetime
+                .naive_local()
+                .date()
+        }
+        None => base.naive_local().date(),
+    };
+
+    let day = format!("{}", date.format("%a %Y-%m-%d"));
+    if calendar.is_business_day(date) {
+        println!("{} is a business day", day.bold());
+    } else {
+        let reason = calendar.holiday(date).unwrap_or("the weekend");
+        println!("{} is not a business day, it's {}", day.bold(), reason);
+    }
+
+    let around = |amount: i64| {
+        calendar
+            .add_business_days(date, amount)
+            .map(|x| format!("{}", x.format("%a %Y-%m-%d")))
+            .unwrap_or_else(|| s!("none"))
+    };
+    println!("  ├── Previous business day: {}", around(-1).bold());
+    println!("  └── Next business day: {}", around(1).bold());
+
+    Ok(())
+}
@@ -0,0 +1,294 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use chrono_tz::{OffsetComponents, Tz, TZ_VARIANTS};
+use clap::ArgMatches;
+use colored::*;
+use std::collections::HashMap;
+use toolkit::time::{abbreviation_offset, find_abbreviation, ABBREVIATIONS};
+
+use crate::commands::CliError;
+
+/// The tz database's list of zones by country, and the names of the countries,
+/// used to say which country a zone is in.
+const ZONE_TABLE: &str = include_str!("../../../resources/zones/zone.tab");
+const COUNTRY_TABLE: &str = include_str!("../../../resources/zones/iso3166.tab");
+
+/// How far ahead to look for the next time a zone's clocks change.
+const TRANSITION_SEARCH_DAYS: i64 = 400;
+
+/// A zone, and the country it's in if it's a place.
+#[derive(Debug, PartialEq)]
+struct ZoneEntry {
+    zone: Tz,
+    country_code: Option<String>,
+    country: Option<String>,
+}
+
+/// What a zone's clocks say at a moment, and when they'll change next.
+#[derive(Debug, PartialEq)]
+pub struct ZoneStatus {
+    pub offset: FixedOffset,
+    pub abbreviation: String,
+    pub dst: bool,
+    pub next_change: Option<DateTime<Utc>>,
+}
+
+pub fn do_time_zones_command(args: &ArgMatches) -> Result<(), CliError> {
+    if args.is_present("abbreviations") {
+        render_abbreviations();
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let entries = load_zones();
+    let query = args.value_of("QUERY");
+
+    let found: Vec<&ZoneEntry> = match query {
+        Some(query) => entries
+            .iter()
+            .filter(|x| matches_query(x, query, &now))
+            .collect(),
+        None => entries.iter().collect(),
+    };
+
+    if found.is_empty() {
+        error!("No time zone matches `{}`", query.unwrap_or_default());
+        return Err(CliError::new("Unknown time zone", 1));
+    }
+
+    let abbreviation = query.map(|x| x.trim().to_uppercase()).unwrap_or_default();
+    if let Some((offset, true)) = abbreviation_offset(&abbreviation) {
+        println!(
+            "{} is ambiguous, it's used for more than one offset. Times written with it are read as {}.\n",
+            abbreviation.bold(),
+            s!(offset).bold()
+        );
+    }
+
+    for entry in found {
+        println!("{}", render_entry(entry, &zone_status(entry.zone, &now)));
+    }
+
+    Ok(())
+}
+
+/// Every zone in the tz database, including links like `US/Pacific` and zones
+/// that aren't places like `UTC` and `Etc/GMT+5`, with the country the zone
+/// table puts it in.
+fn load_zones() -> Vec<ZoneEntry> {
+    let countries: HashMap<&str, &str> = COUNTRY_TABLE
+        .lines()
+        .filter(|x| !x.starts_with('#'))
+        .filter_map(|x| {
+            let mut parts = x.split('\t');
+            Some((parts.next()?, parts.next()?))
+        })
+        .collect();
+
+    let zone_countries: HashMap<&str, &str> = ZONE_TABLE
+        .lines()
+        .filter(|x| !x.starts_with('#'))
+        .filter_map(|x| {
+            let parts: Vec<&str> = x.split('\t').collect();
+            Some((*parts.get(2)?, parts[0]))
+        })
+        .collect();
+
+    let mut entries: Vec<ZoneEntry> = TZ_VARIANTS
+        .iter()
+        .map(|zone| {
+            let country_code = zone_countries.get(zone.name());
+            ZoneEntry {
+                zone: *zone,
+                country_code: country_code.map(|x| s!(x)),
+                country: country_code.and_then(|x| countries.get(x)).map(|x| s!(x)),
+            }
+        })
+        .collect();
+
+    entries.sort_by_key(|x| x.zone.name());
+    entries
+}
+
+/// A zone matches an abbreviation it could mean or is using now, or a
+/// country code it's in. Anything else matches part of the zone's name
+/// (`new york`) or part of its country's name.
+fn matches_query(entry: &ZoneEntry, query: &str, now: &DateTime<Utc>) -> bool {
+    let query = query.trim();
+    let lower = query.to_lowercase();
+    let upper = query.to_uppercase();
+    let abbreviation = format!("{}", now.with_timezone(&entry.zone).format("%Z"));
+
+    if let Some(meanings) = find_abbreviation(&upper) {
+        return meanings.iter().any(|(_, zone)| *zone == entry.zone) || abbreviation == upper;
+    }
+
+    if query.len() == 2
+        && COUNTRY_TABLE
+            .lines()
+            .any(|x| x.starts_with(&format!("{}\t", upper)))
+    {
+        return entry.country_code.as_ref() == Some(&upper);
+    }
+
+    entry
+        .zone
+        .name()
+        .to_lowercase()
+        .contains(&lower.replace(' ', "_"))
+        || entry
+            .country
+            .iter()
+            .any(|x| x.to_lowercase().contains(&lower))
+        || abbreviation == upper
+}
+
+pub fn zone_status(zone: Tz, now: &DateTime<Utc>) -> ZoneStatus {
+    let local = now.with_timezone(&zone);
+
+    ZoneStatus {
+        offset: local.offset().fix(),
+        abbreviation: format!("{}", local.format("%Z")),
+        dst: local.offset().dst_offset() > Duration::zero(),
+        next_change: next_change(&zone, now),
+    }
+}
+
+/// The first instant after `now` where the zone's offset is different. Steps a
+/// day at a time, then narrows down to the second.
+pub fn next_change<Z: TimeZone>(zone: &Z, now: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let offset_at = |instant: DateTime<Utc>| instant.with_timezone(zone).offset().fix();
+    let start = Utc.timestamp(now.timestamp(), 0);
+    let current = offset_at(start);
+
+    let mut after = (1..=TRANSITION_SEARCH_DAYS)
+        .map(|x| start + Duration::days(x))
+        .find(|x| offset_at(*x) != current)?;
+    let mut before = after - Duration::days(1);
+
+    while after - before > Duration::seconds(1) {
+        let middle = before + Duration::seconds((after - before).num_seconds() / 2);
+        if offset_at(middle) == current {
+            before = middle;
+        } else {
+            after = middle;
+        }
+    }
+
+    Some(after)
+}
+
+fn render_entry(entry: &ZoneEntry, status: &ZoneStatus) -> String {
+    let country = match (&entry.country_code, &entry.country) {
+        (Some(code), Some(name)) => format!("{} {}", code, name),
+        (Some(code), None) => s!(code),
+        _ => s!(""),
+    };
+
+    let next_change = match status.next_change {
+        Some(at) => {
+            let after = at.with_timezone(&entry.zone);
+            format!(
+                "changes {} to UTC{} {}",
+                at.format("%Y-%m-%d %H:%M:%S UTC"),
+                after.offset().fix(),
+                after.format("%Z")
+            )
+        }
+        None => s!("no changes coming up"),
+    };
+
+    format!(
+        "{} {:<36} UTC{} {:<6} {:<15} {}",
+        format!("{:<32}", entry.zone.name()).bold(),
+        country,
+        status.offset,
+        status.abbreviation,
+        if status.dst {
+            "daylight saving"
+        } else {
+            "standard time"
+        },
+        next_change
+    )
+}
+
+fn render_abbreviations() {
+    for (abbreviation, meanings) in ABBREVIATIONS.iter() {
+        let names: Vec<String> = meanings
+            .iter()
+            .map(|(offset, zone)| format!("{} {}", zone.name(), FixedOffset::east(*offset)))
+            .collect();
+        let note = match abbreviation_offset(abbreviation) {
+            Some((offset, true)) => format!(" (ambiguous, read as {})", offset),
+            _ => s!(""),
+        };
+
+        println!(
+            "{} {}{}",
+            format!("{:<5}", abbreviation).bold(),
+            names.join(", "),
+            note
+        );
+    }
+}
+
+#[test]
+fn zone_search_and_status() {
+    let now = Utc.ymd(2018, 12, 4).and_hms(4, 20, 22);
+    let entries = load_zones();
+    let find = |query: &str| -> Vec<Tz> {
+        entries
+            .iter()
+            .filter(|x| matches_query(x, query, &now))
+            .map(|x| x.zone)
+            .collect()
+    };
+
+    let new_york = entries
+        .iter()
+        .find(|x| x.zone == chrono_tz::America::New_York)
+        .unwrap();
+    assert_eq!(Some(s!("United States")), new_york.country);
+    assert!(entries.iter().any(|x| x.zone == Tz::PST8PDT));
+    assert!(entries.iter().any(|x| x.zone == Tz::US__Pacific));
+    assert!(entries.iter().any(|x| x.zone == Tz::Etc__GMTPlus5));
+
+    assert_eq!(vec![chrono_tz::America::New_York], find("new york"));
+    assert_eq!(find("germany"), find("de"));
+    assert!(find("de").contains(&chrono_tz::Europe::Berlin));
+    assert_eq!(
+        vec![
+            chrono_tz::Asia::Calcutta,
+            chrono_tz::Asia::Jerusalem,
+            chrono_tz::Asia::Kolkata,
+            chrono_tz::Asia::Tel_Aviv,
+            chrono_tz::Europe::Dublin,
+            Tz::Israel
+        ],
+        find("ist")
+    );
+
+    assert_eq!(
+        ZoneStatus {
+            offset: FixedOffset::west(5 * 3600),
+            abbreviation: s!("EST"),
+            dst: false,
+            next_change: Some(Utc.ymd(2019, 3, 10).and_hms(7, 0, 0)),
+        },
+        zone_status(chrono_tz::America::New_York, &now)
+    );
+    assert_eq!(
+        ZoneStatus {
+            offset: FixedOffset::east(11 * 3600),
+            abbreviation: s!("AEDT"),
+            dst: true,
+            next_change: Some(Utc.ymd(2019, 4, 6).and_hms(16, 0, 0)),
+        },
+        zone_status(chrono_tz::Australia::Sydney, &now)
+    );
+    assert_eq!(
+        None,
+        zone_status(chrono_tz::Asia::Kolkata, &now).next_change
+    );
+}
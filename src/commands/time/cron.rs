@@ -0,0 +1,1058 @@
+use chrono::naive::NaiveTime;
+use chrono::prelude::*;
+use chrono::{Duration, LocalResult};
+use chrono_tz::Tz;
+use clap::ArgMatches;
+use colored::*;
+
+use super::catalog;
+use crate::commands::CliError;
+
+/// How far ahead to look for the next run before giving up, which is long
+/// enough to find a run on Feb 29 across a skipped leap year.
+const SEARCH_YEARS: i32 = 9;
+
+/// How far ahead to look for clock changes that skip or repeat runs.
+const DST_SEARCH_DAYS: i64 = 366;
+
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Kind {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Weekday,
+    Year,
+}
+
+impl Kind {
+    /// The values that can be written in the field. Quartz counts weekdays
+    /// from 1 on Sunday, everything else from 0 with 7 also meaning Sunday.
+    fn bounds(self, quartz: bool) -> (u32, u32) {
+        match self {
+            Kind::Second | Kind::Minute => (0, 59),
+            Kind::Hour => (0, 23),
+            Kind::Day => (1, 31),
+            Kind::Month => (1, 12),
+            Kind::Weekday if quartz => (1, 7),
+            Kind::Weekday => (0, 7),
+            Kind::Year => (1970, 2099),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Second => "second",
+            Kind::Minute => "minute",
+            Kind::Hour => "hour",
+            Kind::Day => "day of month",
+            Kind::Month => "month",
+            Kind::Weekday => "day of week",
+            Kind::Year => "year",
+        }
+    }
+}
+
+/// Day rules that can't be written as a list of numbers.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Special {
+    /// `L` in the day of month.
+    LastDay,
+    /// `LW` in the day of month.
+    LastWeekday,
+    /// `15W` in the day of month, the weekday closest to the 15th.
+    NearestWeekday(u32),
+    /// `5L` in the day of week, the last Friday.
+    LastOf(u32),
+    /// `1#2` in the day of week, the second Monday.
+    Nth(u32, u32),
+}
+
+impl Special {
+    fn matches(self, date: NaiveDate) -> bool {
+        let last = last_day(date.year(), date.month());
+        let weekday = date.weekday().num_days_from_sunday();
+        match self {
+            Special::LastDay => date.day() == last,
+            Special::LastWeekday => {
+                let last = NaiveDate::from_ymd(date.year(), date.month(), last);
+                date == match last.weekday() {
+                    Weekday::Sat => last.pred(),
+                    Weekday::Sun => last.pred().pred(),
+                    _ => last,
+                }
+            }
+            Special::NearestWeekday(day) if day <= last => {
+                let target = NaiveDate::from_ymd(date.year(), date.month(), day);
+                date == match target.weekday() {
+                    Weekday::Sat if day == 1 => target.succ().succ(),
+                    Weekday::Sat => target.pred(),
+                    Weekday::Sun if day == last => target.pred().pred(),
+                    Weekday::Sun => target.succ(),
+                    _ => target,
+                }
+            }
+            Special::NearestWeekday(_) => false,
+            Special::LastOf(day) => weekday == day && date.day() + 7 > last,
+            Special::Nth(day, nth) => weekday == day && (date.day() - 1) / 7 + 1 == nth,
+        }
+    }
+}
+
+/// One field of an expression. Weekdays are stored counting from 0 on Sunday
+/// whichever way they were written.
+#[derive(Debug, PartialEq, Clone)]
+struct Field {
+    values: Vec<u32>,
+    specials: Vec<Special>,
+    /// Written as `*` or `?`, which changes how the two day fields combine.
+    star: bool,
+}
+
+impl Field {
+    fn parse(input: &str, kind: Kind, quartz: bool) -> Result<Self, String> {
+        let (min, max) = kind.bounds(quartz);
+        let mut field = Field {
+            values: Vec::new(),
+            specials: Vec::new(),
+            star: input.starts_with('*') || input == "?",
+        };
+
+        if input == "?" && kind != Kind::Day && kind != Kind::Weekday {
+            return Err(format!("`?` can't be used in the {} field", kind.name()));
+        }
+
+        for part in input.split(',') {
+            if let Some(special) = parse_special(part, kind, quartz)? {
+                field.specials.push(special);
+                continue;
+            }
+
+            let (range, step) = match part.find('/') {
+                Some(index) => match part[index + 1..].parse::<u32>() {
+                    Ok(step) if step > 0 => (&part[..index], Some(step)),
+                    _ => return Err(format!("`{}` has an invalid step", part)),
+                },
+                None => (part, None),
+            };
+
+            let (start, end) = if range == "*" || range == "?" {
+                (min, max)
+            } else if let Some(index) = range.find('-') {
+                (
+                    parse_value(&range[..index], kind, quartz)?,
+                    parse_value(&range[index + 1..], kind, quartz)?,
+                )
+            } else {
+                let value = parse_value(range, kind, quartz)?;
+                (value, if step.is_some() { max } else { value })
+            };
+
+            if start > end {
+                return Err(format!("`{}` runs backwards", part));
+            }
+
+            let step = step.unwrap_or(1) as usize;
+            for value in (start..=end).step_by(step) {
+                field.values.push(normalize(value, kind, quartz));
+            }
+        }
+
+        field.values.sort();
+        field.values.dedup();
+        Ok(field)
+    }
+
+    fn contains(&self, value: u32) -> bool {
+        self.values.binary_search(&value).is_ok()
+    }
+
+    fn matches(&self, value: u32, date: NaiveDate) -> bool {
+        self.contains(value) || self.specials.iter().any(|x| x.matches(date))
+    }
+}
+
+fn parse_value(input: &str, kind: Kind, quartz: bool) -> Result<u32, String> {
+    let (min, max) = kind.bounds(quartz);
+    let upper = input.to_uppercase();
+    let named = match kind {
+        Kind::Month => MONTHS
+            .iter()
+            .position(|x| *x == upper)
+            .map(|x| x as u32 + 1),
+        Kind::Weekday => WEEKDAYS
+            .iter()
+            .position(|x| *x == upper)
+            .map(|x| x as u32 + min),
+        _ => None,
+    };
+
+    match named.or_else(|| input.parse().ok()) {
+        Some(value) if value >= min && value <= max => Ok(value),
+        _ => Err(format!(
+            "`{}` isn't a {} between {} and {}",
+            input,
+            kind.name(),
+            min,
+            max
+        )),
+    }
+}
+
+fn normalize(value: u32, kind: Kind, quartz: bool) -> u32 {
+    match kind {
+        Kind::Weekday if quartz => value - 1,
+        Kind::Weekday => value % 7,
+        _ => value,
+    }
+}
+
+fn parse_special(input: &str, kind: Kind, quartz: bool) -> Result<Option<Special>, String> {
+    let upper = input.to_uppercase();
+    let without_last = |x: &str| x[..x.len() - 1].to_string();
+
+    let special = match kind {
+        Kind::Day if upper == "L" => Special::LastDay,
+        Kind::Day if upper == "LW" => Special::LastWeekday,
+        Kind::Day if upper.len() > 1 && upper.ends_with('W') => {
+            Special::NearestWeekday(parse_value(&without_last(&upper), kind, quartz)?)
+        }
+        Kind::Weekday if upper.len() > 1 && upper.ends_with('L') => {
+            let day = parse_value(&without_last(&upper), kind, quartz)?;
+            Special::LastOf(normalize(day, kind, quartz))
+        }
+        Kind::Weekday if upper.contains('#') => {
+            let index = upper.find('#').unwrap();
+            let day = parse_value(&upper[..index], kind, quartz)?;
+            match upper[index + 1..].parse::<u32>() {
+                Ok(nth) if (1..=5).contains(&nth) => {
+                    Special::Nth(normalize(day, kind, quartz), nth)
+                }
+                _ => return Err(format!("`{}` needs a week between 1 and 5", input)),
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(special))
+}
+
+/// A parsed cron expression.
+#[derive(Debug, PartialEq)]
+struct Schedule {
+    seconds: Field,
+    minutes: Field,
+    hours: Field,
+    days: Field,
+    months: Field,
+    weekdays: Field,
+    years: Option<Field>,
+}
+
+impl Schedule {
+    /// Parse a 5 field expression, a 6 field one starting with seconds, a
+    /// Quartz one (with `?` or a 7th year field) or a macro like `@daily`.
+    fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        let expanded = match input.to_lowercase().as_str() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            x if x.starts_with('@') => return Err(format!("`{}` isn't a known macro", input)),
+            _ => input,
+        };
+
+        let mut fields: Vec<&str> = expanded.split_whitespace().collect();
+        let quartz = fields.len() == 7 || fields.contains(&"?");
+        match fields.len() {
+            5 => fields.insert(0, "0"),
+            6 | 7 => {}
+            x => return Err(format!("expected 5, 6 or 7 fields but found {}", x)),
+        }
+
+        if quartz && fields[3] != "?" && fields[5] != "?" {
+            return Err(s!(
+                "Quartz expressions need `?` in the day of month or day of week field"
+            ));
+        }
+
+        Ok(Schedule {
+            seconds: Field::parse(fields[0], Kind::Second, quartz)?,
+            minutes: Field::parse(fields[1], Kind::Minute, quartz)?,
+            hours: Field::parse(fields[2], Kind::Hour, quartz)?,
+            days: Field::parse(fields[3], Kind::Day, quartz)?,
+            months: Field::parse(fields[4], Kind::Month, quartz)?,
+            weekdays: Field::parse(fields[5], Kind::Weekday, quartz)?,
+            years: match fields.get(6) {
+                Some(years) => Some(Field::parse(years, Kind::Year, quartz)?),
+                None => None,
+            },
+        })
+    }
+
+    /// Like cron, a day runs when it matches both day fields if either is
+    /// `*`, and when it matches either of them otherwise.
+    fn runs_on(&self, date: NaiveDate) -> bool {
+        let day = self.days.matches(date.day(), date);
+        let weekday = self
+            .weekdays
+            .matches(date.weekday().num_days_from_sunday(), date);
+
+        if self.days.star || self.weekdays.star {
+            day && weekday
+        } else {
+            day || weekday
+        }
+    }
+
+    /// The first time of day at or after `from` the schedule runs.
+    fn first_time(&self, from: NaiveTime) -> Option<NaiveTime> {
+        let from = (from.hour(), from.minute(), from.second());
+        for hour in self.hours.values.iter().filter(|x| **x >= from.0) {
+            for minute in self.minutes.values.iter() {
+                for second in self.seconds.values.iter() {
+                    if (*hour, *minute, *second) >= from {
+                        return Some(NaiveTime::from_hms(*hour, *minute, *second));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// The next wall clock time after `after` the schedule runs.
+    fn next_after(&self, after: &NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_nanosecond(0)? + Duration::seconds(1);
+        let midnight = NaiveTime::from_hms(0, 0, 0);
+        let limit = start.year() + SEARCH_YEARS;
+
+        let mut date = start.date();
+        let mut from = start.time();
+        while date.year() <= limit {
+            if !self.months.contains(date.month()) {
+                date = match date.month() {
+                    12 => NaiveDate::from_ymd(date.year() + 1, 1, 1),
+                    month => NaiveDate::from_ymd(date.year(), month + 1, 1),
+                };
+                from = midnight;
+                continue;
+            }
+
+            if !self.years.iter().all(|x| x.contains(date.year() as u32)) {
+                date = NaiveDate::from_ymd(date.year() + 1, 1, 1);
+                from = midnight;
+                continue;
+            }
+
+            if self.runs_on(date) {
+                if let Some(time) = self.first_time(from) {
+                    return Some(date.and_time(time));
+                }
+            }
+
+            date = date.succ();
+            from = midnight;
+        }
+
+        None
+    }
+
+    /// How many runs fall in `[start, end)` on the wall clock.
+    fn runs_between(&self, start: &NaiveDateTime, end: &NaiveDateTime) -> usize {
+        let mut count = 0;
+        let mut cursor = *start - Duration::seconds(1);
+        while let Some(next) = self.next_after(&cursor) {
+            if next >= *end {
+                break;
+            }
+            count += 1;
+            cursor = next;
+        }
+        count
+    }
+
+    fn describe(&self) -> String {
+        let mut parts = vec![self.describe_time()];
+        parts.extend(self.describe_days());
+
+        match shape(&self.months.values, 1, 12) {
+            Shape::Every => {}
+            Shape::Range(start, end) => parts.push(format!(
+                "in {} through {}",
+                month_name(start),
+                month_name(end)
+            )),
+            _ => {
+                let names = self
+                    .months
+                    .values
+                    .iter()
+                    .map(|x| s!(month_name(*x)))
+                    .collect();
+                parts.push(format!("in {}", join(names)));
+            }
+        }
+
+        if let Some(years) = &self.years {
+            match shape(&years.values, 1970, 2099) {
+                Shape::Every => {}
+                Shape::Range(start, end) => parts.push(format!("in {} through {}", start, end)),
+                Shape::Step(step, start) => {
+                    parts.push(format!("every {} years from {}", step, start))
+                }
+                _ => parts.push(format!("in {}", join(numbers(&years.values)))),
+            }
+        }
+
+        let sentence = parts.join(", ");
+        let mut chars = sentence.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => sentence,
+        }
+    }
+
+    fn describe_time(&self) -> String {
+        let seconds = shape(&self.seconds.values, 0, 59);
+        let minutes = shape(&self.minutes.values, 0, 59);
+        let hours = shape(&self.hours.values, 0, 23);
+
+        if let (Shape::One(second), Shape::One(minute)) = (&seconds, &minutes) {
+            let times = match &hours {
+                Shape::One(hour) => Some(vec![*hour]),
+                Shape::List(hours) => Some(hours.clone()),
+                _ => None,
+            };
+
+            if let Some(times) = times {
+                let times = times
+                    .iter()
+                    .map(|hour| match second {
+                        0 => format!("{:02}:{:02}", hour, minute),
+                        _ => format!("{:02}:{:02}:{:02}", hour, minute, second),
+                    })
+                    .collect();
+                return format!("at {}", join(times));
+            }
+        }
+
+        let mut phrase = match seconds {
+            Shape::One(0) => describe_unit(&minutes, "minute", true),
+            Shape::Every | Shape::Step(_, _) if minutes == Shape::Every => {
+                describe_unit(&seconds, "second", true)
+            }
+            _ => format!(
+                "{} {} {}",
+                describe_unit(&seconds, "second", true),
+                connector(&seconds),
+                describe_unit(&minutes, "minute", false)
+            ),
+        };
+
+        let last = if minutes == Shape::Every && seconds != Shape::One(0) {
+            &seconds
+        } else {
+            &minutes
+        };
+        match (&hours, last) {
+            (Shape::Every, Shape::One(_)) => phrase.push_str(" past every hour"),
+            (Shape::Every, _) => {}
+            _ => phrase.push_str(&format!(
+                " {} {}",
+                connector(last),
+                describe_unit(&hours, "hour", false)
+            )),
+        }
+
+        phrase
+    }
+
+    fn describe_days(&self) -> Option<String> {
+        let days = if shape(&self.days.values, 1, 31) == Shape::Every {
+            None
+        } else {
+            let mut items = match shape(&self.days.values, 1, 31) {
+                Shape::One(day) => vec![format!("day {}", day)],
+                Shape::Range(start, end) => vec![format!("days {} through {}", start, end)],
+                Shape::Step(step, 1) => vec![format!("every {} day", ordinal(step))],
+                Shape::List(ref days) if days.is_empty() => vec![],
+                _ => vec![format!("days {}", join(numbers(&self.days.values)))],
+            };
+            items.extend(self.days.specials.iter().map(|x| match x {
+                Special::LastDay => s!("the last day"),
+                Special::LastWeekday => s!("the last weekday"),
+                Special::NearestWeekday(day) => format!("the weekday nearest day {}", day),
+                _ => s!(""),
+            }));
+            Some(format!("on {} of the month", join(items)))
+        };
+
+        let weekdays = if shape(&self.weekdays.values, 0, 6) == Shape::Every {
+            None
+        } else {
+            let mut items = match shape(&self.weekdays.values, 0, 6) {
+                Shape::Range(start, end) => vec![format!(
+                    "{} through {}",
+                    weekday_name(start),
+                    weekday_name(end)
+                )],
+                _ => self
+                    .weekdays
+                    .values
+                    .iter()
+                    .map(|x| s!(weekday_name(*x)))
+                    .collect(),
+            };
+            items.extend(self.weekdays.specials.iter().map(|x| match x {
+                Special::LastOf(day) => format!("the last {} of the month", weekday_name(*day)),
+                Special::Nth(day, nth) => {
+                    format!("the {} {} of the month", ordinal(*nth), weekday_name(*day))
+                }
+                _ => s!(""),
+            }));
+            Some(format!("on {}", join(items)))
+        };
+
+        match (days, weekdays) {
+            (Some(days), Some(weekdays)) if self.days.star || self.weekdays.star => {
+                Some(format!("{} and {}", days, weekdays))
+            }
+            (Some(days), Some(weekdays)) => Some(format!("{} or {}", days, weekdays)),
+            (days, weekdays) => days.or(weekdays),
+        }
+    }
+}
+
+/// How the values in a field are spread out, for describing them.
+#[derive(Debug, PartialEq)]
+enum Shape {
+    Every,
+    One(u32),
+    /// Every `step` values, starting from the given one.
+    Step(u32, u32),
+    Range(u32, u32),
+    List(Vec<u32>),
+}
+
+fn shape(values: &[u32], min: u32, max: u32) -> Shape {
+    if values.len() as u32 == max - min + 1 {
+        return Shape::Every;
+    }
+
+    match values.len() {
+        0 => return Shape::List(vec![]),
+        1 => return Shape::One(values[0]),
+        _ => {}
+    }
+
+    let first = values[0];
+    let last = values[values.len() - 1];
+    let step = values[1] - first;
+    let even = values.windows(2).all(|x| x[1] - x[0] == step);
+
+    if even && step == 1 && values.len() > 2 {
+        Shape::Range(first, last)
+    } else if even && step > 1 && first < min + step && last + step > max {
+        Shape::Step(step, first)
+    } else {
+        Shape::List(values.to_vec())
+    }
+}
+
+/// A field of times, like `every 15 minutes` or `hours 9 through 17`. The
+/// leading field reads `at minute 5` where a later one reads `minute 5`.
+fn describe_unit(shape: &Shape, unit: &str, leading: bool) -> String {
+    let at = if leading { "at " } else { "" };
+    match shape {
+        Shape::Every => format!("every {}", unit),
+        Shape::One(value) => format!("{}{} {}", at, unit, value),
+        Shape::Step(step, 0) => format!("every {} {}s", step, unit),
+        Shape::Step(step, start) => {
+            format!("every {} {}s starting at {} {}", step, unit, unit, start)
+        }
+        Shape::Range(start, end) if leading => {
+            format!("every {} from {} through {}", unit, start, end)
+        }
+        Shape::Range(start, end) => format!("{}s {} through {}", unit, start, end),
+        Shape::List(values) => format!("{}{}s {}", at, unit, join(numbers(values))),
+    }
+}
+
+/// `at minute 0 past every hour`, but `every minute of hour 9`.
+fn connector(shape: &Shape) -> &'static str {
+    match shape {
+        Shape::One(_) => "past",
+        _ => "of",
+    }
+}
+
+fn month_name(month: u32) -> &'static str {
+    [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ][month as usize - 1]
+}
+
+fn weekday_name(day: u32) -> &'static str {
+    [
+        "Sunday",
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+    ][day as usize]
+}
+
+fn ordinal(value: u32) -> String {
+    let suffix = match (value % 10, value % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", value, suffix)
+}
+
+fn numbers(values: &[u32]) -> Vec<String> {
+    values.iter().map(|x| format!("{}", x)).collect()
+}
+
+/// `a`, `a and b`, `a, b and c`.
+fn join(items: Vec<String>) -> String {
+    match items.len() {
+        0 => s!(""),
+        1 => items[0].clone(),
+        x => format!("{} and {}", items[..x - 1].join(", "), items[x - 1]),
+    }
+}
+
+fn last_day(year: i32, month: u32) -> u32 {
+    let next = match month {
+        12 => NaiveDate::from_ymd(year + 1, 1, 1),
+        month => NaiveDate::from_ymd(year, month + 1, 1),
+    };
+    next.pred().day()
+}
+
+/// Runs that a clock change in the scheduler's zone skips or repeats.
+#[derive(Debug, PartialEq)]
+enum DstEffect {
+    /// The clocks jumped from the first time to the second, so runs between
+    /// them never happen.
+    Skipped(NaiveDateTime, NaiveTime, usize),
+    /// The clocks went back from the first time to the second, so runs
+    /// between them happen twice.
+    Repeated(NaiveDateTime, NaiveTime, usize),
+}
+
+pub fn do_time_cron_command(args: &ArgMatches) -> Result<(), CliError> {
+    let input: Vec<&str> = args.values_of("EXPRESSION").unwrap().collect();
+    let expression = input.join(" ");
+    let schedule = match Schedule::parse(&expression) {
+        Ok(schedule) => schedule,
+        Err(reason) => {
+            error!(
+                "Unable to understand cron expression `{}`: {}",
+                expression, reason
+            );
+            return Err(CliError::new("Unknown format", 1));
+        }
+    };
+
+    let count = match args.value_of("count").unwrap_or("5").parse::<usize>() {
+        Ok(count) if count > 0 => count,
+        _ => {
+            error!("Count must be a positive number");
+            return Err(CliError::new("Format error", 2));
+        }
+    };
+
+    let base = super::parse_base(args)?;
+    let from = match args.value_of("from") {
+        Some(from) => {
+            let holidays = super::business::holidays_for(from, args)?;
+            super::parse_one(from, &base, &holidays, args)?.datetime
+        }
+        None => base,
+    }
+    .with_timezone(&Utc);
+    let zones = super::zones::display_zones(args)?;
+
+    match args.value_of("in_zone") {
+        Some(zone) => {
+            let zone = super::parse_zone(zone)?;
+            render_schedule(&schedule, &zone, Some(zone), &from, count, &zones);
+        }
+        None => render_schedule(&schedule, &Local, None, &from, count, &zones),
+    }
+
+    Ok(())
+}
+
+fn render_schedule<Z: TimeZone>(
+    schedule: &Schedule,
+    zone: &Z,
+    named: Option<Tz>,
+    from: &DateTime<Utc>,
+    count: usize,
+    zones: &[Tz],
+) {
+    println!("{}", schedule.describe().bold());
+    println!(
+        "  ├── Runs in: {}",
+        named.map_or("the local zone", |x| x.name()).bold()
+    );
+
+    let effects = dst_effects(schedule, zone, from);
+    let note = if catalog::next_change(zone, from).is_none() {
+        s!("The clocks don't change in this zone")
+    } else if effects.is_empty() {
+        s!("Clock changes in the next year don't skip or repeat any runs")
+    } else {
+        s!("Clock changes in the next year affect some runs")
+    };
+    let branch = if effects.is_empty() {
+        "└──"
+    } else {
+        "├──"
+    };
+    println!("  {} Daylight saving: {}", branch, note.bold());
+
+    for (index, effect) in effects.iter().enumerate() {
+        let branch = if index + 1 == effects.len() {
+            "└──"
+        } else {
+            "├──"
+        };
+        let text = match effect {
+            DstEffect::Skipped(at, to, runs) => format!(
+                "Skips {} run(s) on {}, the clocks jump from {} to {}",
+                runs,
+                at.format("%a %Y-%m-%d"),
+                at.format("%H:%M"),
+                to.format("%H:%M")
+            ),
+            DstEffect::Repeated(at, to, runs) => format!(
+                "Repeats {} run(s) on {}, the clocks go back from {} to {}",
+                runs,
+                at.format("%a %Y-%m-%d"),
+                at.format("%H:%M"),
+                to.format("%H:%M")
+            ),
+        };
+        println!("    {} {}", branch, text.yellow().bold());
+    }
+
+    let runs = next_runs(schedule, zone, from, count);
+    if runs.is_empty() {
+        println!(
+            "\n{}",
+            format!("Never runs in the next {} years", SEARCH_YEARS).bold()
+        );
+    }
+
+    for (index, (datetime, note)) in runs.iter().enumerate() {
+        println!();
+        println!(
+            "Run {} of {} is at {}",
+            index + 1,
+            runs.len(),
+            format!("{}", datetime).bold()
+        );
+        if let Some(note) = note {
+            println!("  ├── Note: {}", note.bold());
+        }
+        super::render_details(datetime, named, zones);
+    }
+}
+
+/// The next `count` runs after `from`. A run in a gap when the clocks go
+/// forward never happens, and a run in the hour the clocks go back happens
+/// twice.
+fn next_runs<Z: TimeZone>(
+    schedule: &Schedule,
+    zone: &Z,
+    from: &DateTime<Utc>,
+    count: usize,
+) -> Vec<(DateTime<FixedOffset>, Option<String>)> {
+    let fix = |x: DateTime<Z>| x.with_timezone(&x.offset().fix());
+    let mut cursor = from.with_timezone(zone).naive_local();
+    let mut runs = Vec::new();
+
+    while runs.len() < count {
+        let next = match schedule.next_after(&cursor) {
+            Some(next) => next,
+            None => break,
+        };
+
+        let found = match zone.from_local_datetime(&next) {
+            LocalResult::Single(x) => vec![(fix(x), None)],
+            LocalResult::Ambiguous(first, second) => vec![
+                (
+                    fix(first),
+                    Some(s!("the first of two runs, before the clocks go back")),
+                ),
+                (
+                    fix(second),
+                    Some(s!("the second of two runs, after the clocks go back")),
+                ),
+            ],
+            LocalResult::None => vec![],
+        };
+
+        runs.extend(
+            found
+                .into_iter()
+                .filter(|(x, _)| x.with_timezone(&Utc) > *from),
+        );
+        cursor = next;
+    }
+
+    runs.truncate(count);
+    runs
+}
+
+/// Every clock change in the next year that skips or repeats runs.
+fn dst_effects<Z: TimeZone>(schedule: &Schedule, zone: &Z, from: &DateTime<Utc>) -> Vec<DstEffect> {
+    let offset_at = |instant: DateTime<Utc>| {
+        Duration::seconds(i64::from(
+            instant.with_timezone(zone).offset().fix().local_minus_utc(),
+        ))
+    };
+    let until = *from + Duration::days(DST_SEARCH_DAYS);
+    let mut effects = Vec::new();
+    let mut cursor = *from;
+
+    while let Some(change) = catalog::next_change(zone, &cursor) {
+        if change > until {
+            break;
+        }
+
+        let before = change.naive_utc() + offset_at(change - Duration::seconds(1));
+        let after = change.naive_utc() + offset_at(change);
+        if after > before {
+            let runs = schedule.runs_between(&before, &after);
+            if runs > 0 {
+                effects.push(DstEffect::Skipped(before, after.time(), runs));
+            }
+        } else {
+            let runs = schedule.runs_between(&after, &before);
+            if runs > 0 {
+                effects.push(DstEffect::Repeated(before, after.time(), runs));
+            }
+        }
+
+        cursor = change;
+    }
+
+    effects
+}
+
+#[test]
+fn cron_parsing() {
+    let schedule = Schedule::parse("30 9 * * 1-5").unwrap();
+    assert_eq!(vec![0], schedule.seconds.values);
+    assert_eq!(vec![30], schedule.minutes.values);
+    assert_eq!(vec![1, 2, 3, 4, 5], schedule.weekdays.values);
+    assert_eq!(None, schedule.years);
+
+    // Weekday names, and 7 is also Sunday.
+    assert_eq!(
+        Schedule::parse("0 0 * * SAT,7").unwrap(),
+        Schedule::parse("0 0 * * 6,0").unwrap()
+    );
+    assert_eq!(
+        vec![0, 15, 30, 45],
+        Schedule::parse("*/15 * * * * *").unwrap().seconds.values
+    );
+    assert_eq!(
+        Schedule::parse("@daily").unwrap(),
+        Schedule::parse("0 0 * * *").unwrap()
+    );
+
+    // Quartz counts weekdays from 1 on Sunday.
+    let quartz = Schedule::parse("0 15 10 ? * MON-FRI 2019").unwrap();
+    assert_eq!(vec![1, 2, 3, 4, 5], quartz.weekdays.values);
+    assert_eq!(Some(vec![2019]), quartz.years.map(|x| x.values));
+    assert_eq!(
+        vec![Special::Nth(1, 2)],
+        Schedule::parse("0 0 0 ? * 2#2").unwrap().weekdays.specials
+    );
+
+    assert!(Schedule::parse("* * * *").is_err());
+    assert!(Schedule::parse("60 * * * *").is_err());
+    assert!(Schedule::parse("0 0 * * FRI-MON").is_err());
+    assert!(Schedule::parse("0 0 0 * * MON *").is_err());
+    assert!(Schedule::parse("@fortnightly").is_err());
+
+    let describe = |x: &str| Schedule::parse(x).unwrap().describe();
+    assert_eq!(
+        "At 09:30, on Monday through Friday",
+        describe("30 9 * * 1-5")
+    );
+    assert_eq!("Every minute", describe("* * * * *"));
+    assert_eq!(
+        "Every 15 minutes of hours 9 through 17",
+        describe("*/15 9-17 * * *")
+    );
+    assert_eq!("At minute 0 past every 2 hours", describe("0 */2 * * *"));
+    assert_eq!("At minute 5 past every hour", describe("5 * * * *"));
+    assert_eq!("Every 10 seconds", describe("*/10 * * * * *"));
+    assert_eq!("At 09:00 and 17:00", describe("0 9,17 * * *"));
+    assert_eq!(
+        "At 00:00, on day 1 of the month, in January",
+        describe("@yearly")
+    );
+    assert_eq!(
+        "At 10:15, on the last Friday of the month",
+        describe("0 15 10 ? * 6L")
+    );
+    assert_eq!(
+        "At 12:00, on day 1 of the month or on Monday",
+        describe("0 12 1 * MON")
+    );
+}
+
+#[test]
+fn cron_next_runs() {
+    let at = |y, m, d, h, mi, s| NaiveDate::from_ymd(y, m, d).and_hms(h, mi, s);
+    let runs = |expression: &str, after: NaiveDateTime, count: usize| {
+        let schedule = Schedule::parse(expression).unwrap();
+        let mut cursor = after;
+        let mut found = Vec::new();
+        for _ in 0..count {
+            cursor = schedule.next_after(&cursor).unwrap();
+            found.push(cursor);
+        }
+        found
+    };
+
+    // Tuesday, so the next weekday run is later that day.
+    let start = at(2018, 12, 4, 4, 20, 22);
+    assert_eq!(
+        vec![at(2018, 12, 4, 9, 30, 0), at(2018, 12, 5, 9, 30, 0)],
+        runs("30 9 * * 1-5", start, 2)
+    );
+    assert_eq!(
+        vec![at(2018, 12, 7, 9, 30, 0), at(2018, 12, 10, 9, 30, 0)],
+        runs("30 9 * * 1-5", at(2018, 12, 6, 9, 30, 0), 2)
+    );
+    assert_eq!(
+        vec![at(2018, 12, 4, 4, 20, 30), at(2018, 12, 4, 4, 21, 0)],
+        runs("*/30 * * * * *", start, 2)
+    );
+
+    // Day of month or day of week when both are given.
+    assert_eq!(
+        vec![at(2018, 12, 31, 12, 0, 0), at(2019, 1, 1, 12, 0, 0)],
+        runs("0 12 1 * MON", at(2018, 12, 25, 0, 0, 0), 2)
+    );
+
+    // Quartz day rules.
+    assert_eq!(
+        vec![at(2018, 12, 31, 0, 0, 0), at(2019, 1, 31, 0, 0, 0)],
+        runs("0 0 0 LW * ?", start, 2)
+    );
+    assert_eq!(
+        vec![at(2018, 12, 14, 0, 0, 0), at(2019, 1, 15, 0, 0, 0)],
+        runs("0 0 0 15W * ?", start, 2)
+    );
+    assert_eq!(
+        vec![at(2018, 12, 28, 0, 0, 0), at(2019, 1, 25, 0, 0, 0)],
+        runs("0 0 0 ? * 6L", start, 2)
+    );
+    assert_eq!(
+        vec![at(2018, 12, 10, 0, 0, 0), at(2019, 1, 14, 0, 0, 0)],
+        runs("0 0 0 ? * MON#2", start, 2)
+    );
+    assert_eq!(
+        vec![at(2020, 2, 29, 0, 0, 0), at(2024, 2, 29, 0, 0, 0)],
+        runs("0 0 29 2 *", start, 2)
+    );
+    assert_eq!(
+        None,
+        Schedule::parse("0 0 30 2 *").unwrap().next_after(&start)
+    );
+}
+
+#[test]
+fn cron_across_dst() {
+    let new_york = chrono_tz::America::New_York;
+    let from = Utc.ymd(2018, 12, 4).and_hms(4, 20, 22);
+
+    let schedule = Schedule::parse("30 1,2 * * *").unwrap();
+    assert_eq!(
+        vec![
+            DstEffect::Skipped(
+                NaiveDate::from_ymd(2019, 3, 10).and_hms(2, 0, 0),
+                NaiveTime::from_hms(3, 0, 0),
+                1
+            ),
+            DstEffect::Repeated(
+                NaiveDate::from_ymd(2019, 11, 3).and_hms(2, 0, 0),
+                NaiveTime::from_hms(1, 0, 0),
+                1
+            ),
+        ],
+        dst_effects(&schedule, &new_york, &from)
+    );
+    assert_eq!(
+        Vec::<DstEffect>::new(),
+        dst_effects(&Schedule::parse("0 9 * * *").unwrap(), &new_york, &from)
+    );
+
+    let runs = next_runs(
+        &schedule,
+        &new_york,
+        &Utc.ymd(2019, 3, 9).and_hms(12, 0, 0),
+        3,
+    );
+    let times: Vec<String> = runs.iter().map(|(x, _)| x.to_rfc3339()).collect();
+    assert_eq!(
+        vec![
+            "2019-03-10T01:30:00-05:00",
+            "2019-03-11T01:30:00-04:00",
+            "2019-03-11T02:30:00-04:00"
+        ],
+        times
+    );
+
+    let runs = next_runs(
+        &schedule,
+        &new_york,
+        &Utc.ymd(2019, 11, 3).and_hms(0, 0, 0),
+        3,
+    );
+    let times: Vec<String> = runs.iter().map(|(x, _)| x.to_rfc3339()).collect();
+    assert_eq!(
+        vec![
+            "2019-11-03T01:30:00-04:00",
+            "2019-11-03T01:30:00-05:00",
+            "2019-11-03T02:30:00-05:00"
+        ],
+        times
+    );
+    assert!(runs[1].1.is_some());
+}
@@ -0,0 +1,315 @@
+use clap::ArgMatches;
+
+use chrono::prelude::*;
+use chrono::Duration;
+use colored::*;
+use toolkit::time::{Calendar, Permutation};
+
+use super::overlap::WorkingHours;
+use crate::commands::CliError;
+
+pub const WORK_DAY_START: u32 = 9;
+pub const WORK_DAY_END: u32 = 17;
+
+pub fn do_time_diff_command(args: &ArgMatches) -> Result<(), CliError> {
+    let base = super::parse_base(args)?;
+    let holidays = super::business::load_holidays(args)?;
+    let starts =
+        super::parse_permutations(args.value_of("START").unwrap(), &base, &holidays, args)?;
+    let ends = super::parse_permutations(args.value_of("END").unwrap(), &base, &holidays, args)?;
+    let explain = args.is_present("explain");
+
+    let mut first = true;
+    for start in &starts {
+        let hours = super::overlap::parse_hours_in(args, start.zone)?;
+        for end in &ends {
+            if !first {
+                println!();
+            }
+            first = false;
+
+            render_diff(start, end, explain, &holidays, hours);
+        }
+    }
+
+    Ok(())
+}
+
+fn render_diff(
+    start_permutation: &Permutation,
+    end_permutation: &Permutation,
+    explain: bool,
+    holidays: &Calendar,
+    hours: WorkingHours,
+) {
+    let start = &start_permutation.datetime;
+    let end = &end_permutation.datetime;
+    let duration = end.signed_duration_since(*start);
+
+    println!(
+        "Understood the duration from {} to {}",
+        format!("{}", start).bold(),
+        format!("{}", end).bold()
+    );
+    if explain {
+        println!(
+            "  ├── Start read as: {}",
+            start_permutation.interpretation.bold()
+        );
+        println!(
+            "  ├── End read as: {}",
+            end_permutation.interpretation.bold()
+        );
+    }
+    println!("  ├── Duration: {}", format_duration(&duration).bold());
+    println!("  ├── ISO 8601: {}", format_iso_duration(&duration).bold());
+    println!(
+        "  ├── Business hours: {}",
+        format!(
+            "{} (Mon-Fri {}-{} {})",
+            format_duration(&business_duration(start, end, holidays, hours)),
+            hours.start.format("%H:%M"),
+            hours.end.format("%H:%M"),
+            start.timezone()
+        )
+        .bold()
+    );
+    println!(
+        "  ├── Business days: {}",
+        format!(
+            "{}",
+            holidays.business_days_between(
+                start.naive_local().date(),
+                end.with_timezone(&start.timezone()).naive_local().date()
+            )
+        )
+        .bold()
+    );
+    println!("  └── Totals");
+    println!(
+        "    ├── Days: {}",
+        format!("{:.3}", duration.num_milliseconds() as f64 / 86_400_000f64).bold()
+    );
+    println!(
+        "    ├── Hours: {}",
+        format!("{:.3}", duration.num_milliseconds() as f64 / 3_600_000f64).bold()
+    );
+    println!(
+        "    ├── Minutes: {}",
+        format!("{:.3}", duration.num_milliseconds() as f64 / 60_000f64).bold()
+    );
+    println!(
+        "    ├── Seconds: {}",
+        format!("{}", duration.num_seconds()).bold()
+    );
+    println!(
+        "    └── Milliseconds: {}",
+        format!("{}", duration.num_milliseconds()).bold()
+    );
+}
+
+/// Renders a duration as `-1d 2h 3m 4s 5ms`.
+fn format_duration(duration: &Duration) -> String {
+    let sign = if *duration < Duration::zero() {
+        "-"
+    } else {
+        ""
+    };
+    let total = duration.num_milliseconds().abs();
+
+    format!(
+        "{}{}d {}h {}m {}s {}ms",
+        sign,
+        total / 86_400_000,
+        total / 3_600_000 % 24,
+        total / 60_000 % 60,
+        total / 1_000 % 60,
+        total % 1_000
+    )
+}
+
+/// Renders a duration in ISO 8601 form, e.g. `P1DT2H3M4.005S`. Negative
+/// durations get a leading `-`.
+fn format_iso_duration(duration: &Duration) -> String {
+    let sign = if *duration < Duration::zero() {
+        "-"
+    } else {
+        ""
+    };
+    let total = duration.num_milliseconds().abs();
+
+    let days = total / 86_400_000;
+    let hours = total / 3_600_000 % 24;
+    let minutes = total / 60_000 % 60;
+    let seconds = total / 1_000 % 60;
+    let millis = total % 1_000;
+
+    let mut output = format!("{}P", sign);
+    if days != 0 {
+        output.push_str(&format!("{}D", days));
+    }
+
+    if hours != 0 || minutes != 0 || seconds != 0 || millis != 0 || days == 0 {
+        output.push('T');
+        if hours != 0 {
+            output.push_str(&format!("{}H", hours));
+        }
+        if minutes != 0 {
+            output.push_str(&format!("{}M", minutes));
+        }
+        if millis != 0 {
+            output.push_str(&format!("{}.{:03}S", seconds, millis));
+        } else if seconds != 0 || (hours == 0 && minutes == 0) {
+            output.push_str(&format!("{}S", seconds));
+        }
+    }
+
+    output
+}
+
+/// How much of the span between `start` and `end` falls inside working `hours`
+/// on business days, measured on the wall clock of `start`'s zone.
+fn business_duration(
+    start: &DateTime<FixedOffset>,
+    end: &DateTime<FixedOffset>,
+    holidays: &Calendar,
+    hours: WorkingHours,
+) -> Duration {
+    let (from, to, negative) = if end < start {
+        (end.with_timezone(&start.timezone()), *start, true)
+    } else {
+        (*start, end.with_timezone(&start.timezone()), false)
+    };
+
+    let hours_on = |day: Date<FixedOffset>| {
+        if !holidays.is_business_day(day.naive_local()) {
+            return Duration::zero();
+        }
+
+        let open = day.and_time(hours.start).unwrap().max(from);
+        let close = day.and_time(hours.end).unwrap().min(to);
+        if close > open {
+            close.signed_duration_since(open)
+        } else {
+            Duration::zero()
+        }
+    };
+
+    // The first and last days can be cut short, every business day between
+    // them is a whole working day.
+    let (first, last) = (from.date(), to.date());
+    let total = if first == last {
+        hours_on(first)
+    } else {
+        let between =
+            holidays.business_days_between(first.naive_local(), last.pred().naive_local());
+        hours_on(first)
+            + hours_on(last)
+            + hours.end.signed_duration_since(hours.start) * between as i32
+    };
+
+    if negative {
+        -total
+    } else {
+        total
+    }
+}
+
+#[test]
+fn iso_duration_format() {
+    assert_eq!("PT0S", format_iso_duration(&Duration::zero()));
+    assert_eq!("P1D", format_iso_duration(&Duration::days(1)));
+    assert_eq!(
+        "P1DT2H3M4.005S",
+        format_iso_duration(
+            &(Duration::days(1)
+                + Duration::hours(2)
+                + Duration::minutes(3)
+                + Duration::seconds(4)
+                + Duration::milliseconds(5))
+        )
+    );
+    assert_eq!("-PT1H30M", format_iso_duration(&Duration::minutes(-90)));
+}
+
+#[test]
+fn duration_format() {
+    assert_eq!(
+        "-1d 2h 3m 4s 5ms",
+        format_duration(
+            &-(Duration::days(1)
+                + Duration::hours(2)
+                + Duration::minutes(3)
+                + Duration::seconds(4)
+                + Duration::milliseconds(5))
+        )
+    );
+}
+
+#[test]
+fn business_hours_skip_nights_and_weekends() {
+    let zone = FixedOffset::west(8 * 3600);
+    // Friday 16:00 to Monday 10:00
+    let start = zone.ymd(2018, 12, 7).and_hms(16, 0, 0);
+    let end = zone.ymd(2018, 12, 10).and_hms(10, 0, 0);
+
+    let weekends = Calendar::default();
+    let nine_to_five = WorkingHours::default();
+
+    assert_eq!(
+        Duration::hours(2),
+        business_duration(&start, &end, &weekends, nine_to_five)
+    );
+    assert_eq!(
+        -Duration::hours(2),
+        business_duration(&end, &start, &weekends, nine_to_five)
+    );
+
+    let end_in_utc = (start + Duration::hours(1)).with_timezone(&FixedOffset::east(0));
+    assert_eq!(
+        Duration::hours(1),
+        business_duration(&start, &end_in_utc, &weekends, nine_to_five)
+    );
+
+    // Three whole weeks later adds fifteen working days.
+    let later = end + Duration::weeks(3);
+    assert_eq!(
+        Duration::hours(2 + 15 * 8),
+        business_duration(&start, &later, &weekends, nine_to_five)
+    );
+
+    // Monday off as well leaves just Friday afternoon.
+    let holidays = Calendar::parse("2018-12-10 Office closed").unwrap();
+    assert_eq!(
+        Duration::hours(1),
+        business_duration(&start, &end, &holidays, nine_to_five)
+    );
+}
+
+#[test]
+fn business_hours_follow_the_working_hours() {
+    let zone = FixedOffset::east(0);
+    // Friday 16:00 to Monday 10:00, working 08:30-16:30.
+    let start = zone.ymd(2018, 12, 7).and_hms(16, 0, 0);
+    let end = zone.ymd(2018, 12, 10).and_hms(10, 0, 0);
+    let hours = WorkingHours {
+        start: NaiveTime::from_hms(8, 30, 0),
+        end: NaiveTime::from_hms(16, 30, 0),
+    };
+
+    assert_eq!(
+        Duration::minutes(30 + 90),
+        business_duration(&start, &end, &Calendar::default(), hours)
+    );
+
+    // A week later adds five whole working days.
+    assert_eq!(
+        Duration::minutes(30 + 90) + Duration::hours(5 * 8),
+        business_duration(
+            &start,
+            &(end + Duration::weeks(1)),
+            &Calendar::default(),
+            hours
+        )
+    );
+}
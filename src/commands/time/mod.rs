@@ -1,129 +1,428 @@
-mod parse;
+mod arithmetic;
+mod batch;
+mod business;
+mod catalog;
+mod cron;
+mod diff;
+mod output;
+mod overlap;
+mod pattern;
+mod range;
+mod scan;
+mod snap;
+mod template;
+mod zones;
+
+pub use self::arithmetic::{do_time_add_command, do_time_sub_command};
+pub use self::business::{counts_business_days, do_time_is_business_day_command, load_holidays};
+pub use self::catalog::do_time_zones_command;
+pub use self::cron::do_time_cron_command;
+pub use self::diff::do_time_diff_command;
+pub use self::overlap::do_time_overlap_command;
+pub use self::output::{epoch_nanos, ValueFormat};
+pub use self::pattern::do_time_pattern_command;
+pub use self::range::do_time_range_command;
+pub use self::scan::do_time_scan_command;
+pub use self::snap::{do_time_end_of_command, do_time_round_command, do_time_start_of_command};
 
 use clap::ArgMatches;
 
 use chrono::prelude::*;
+use chrono_tz::Tz;
 use colored::*;
+use std::str::FromStr;
+use toolkit::time::{
+    self, Calendar, DateRule, Encoding, EpochUnit, ParseOptions, Permutation, TimeResult,
+};
 
 use crate::commands::CliError;
-use parse::TimeResult;
+use output::{ToJson, ToTsv, ToYaml};
+use template::OutputTemplate;
 
 pub fn do_time_command(args: &ArgMatches) -> Result<(), CliError> {
+    if let Some(path) = args.value_of("batch") {
+        return batch::do_time_batch(args, path);
+    }
+
     let input_array: Vec<&str> = args.values_of("INPUT").unwrap().collect();
     let input_string = input_array.join(" ");
-    return match (parse::parse(&input_string), args.is_present("utc_only")) {
-        (Ok(date), true) => render_utc(date),
-        (Ok(date), false) => render_full_output(date),
-        (Err(_), _) => {
-            error!("Unable to understand `{}`", input_string);
+    let base = parse_base(args)?;
+    let display_zones = zones::display_zones(args)?;
+    let prefer = preferred_rule(args);
+    let explain = args.is_present("explain");
+    let templates = parse_templates(args)?;
+    let holidays = business::holidays_for(&input_string, args)?;
+    return match (
+        time::parse_with_options(&input_string, &base, parse_options(args, &holidays)),
+        args.is_present("utc_only"),
+        args.value_of("output"),
+    ) {
+        (Ok(date), _, _) if !templates.is_empty() => {
+            render_templates(&choose_permutations(date, prefer), &templates)
+        }
+        (Ok(date), _, Some(format)) => {
+            render_records(choose_permutations(date, prefer), format, &display_zones)
+        }
+        (Ok(date), true, None) => render_utc(date, prefer),
+        (Ok(date), false, None) => {
+            render_permutations(&choose_permutations(date, prefer), &display_zones, explain)
+        }
+        (Err(reason), _, _) => {
+            error!("Unable to understand `{}`: {}", input_string, reason);
             return Err(CliError::new("Unknown format", 1));
         }
     };
 }
 
-fn render_full_output(input: TimeResult) -> Result<(), CliError> {
-    let datetimes = match input {
-        TimeResult::Epoch(epoch) => epoch.make_permutations(),
-        TimeResult::String(string_format) => string_format.make_permutations(),
+/// The date order from `--prefer`, if one was given. `locale` looks at
+/// `LC_ALL`, `LC_TIME` and then `LANG`.
+pub fn preferred_rule(args: &ArgMatches) -> Option<DateRule> {
+    match args.value_of("prefer") {
+        Some("locale") => {
+            let locale = ["LC_ALL", "LC_TIME", "LANG"]
+                .iter()
+                .filter_map(|x| std::env::var(x).ok())
+                .find(|x| !x.is_empty())
+                .unwrap_or_default();
+            Some(DateRule::from_locale(&locale))
+        }
+        Some(prefer) => DateRule::from_preference(prefer),
+        None => None,
+    }
+}
+
+/// `--unit` and `--as`, for when the input shouldn't be guessed at, and the
+/// holidays that business days skip.
+pub fn parse_options<'a>(args: &ArgMatches, holidays: &'a Calendar) -> ParseOptions<'a> {
+    ParseOptions {
+        unit: args.value_of("unit").and_then(EpochUnit::from_name),
+        encoding: args.value_of("as").and_then(Encoding::from_name),
+        holidays: Some(holidays),
+    }
+}
+
+fn choose_permutations(input: TimeResult, prefer: Option<DateRule>) -> Vec<Permutation> {
+    let permutations = input.make_permutations();
+    match prefer {
+        Some(rule) => time::choose_permutation(permutations, rule),
+        None => permutations,
+    }
+}
+
+/// The one reading to use when a command needs a single time: the `--prefer`
+/// order, or else year, month, day when the date was written year first.
+fn choose_one(input: TimeResult, prefer: Option<DateRule>) -> Option<Permutation> {
+    let rule = prefer.unwrap_or(DateRule::YearMonthDay);
+    time::choose_permutation(input.make_permutations(), rule)
+        .into_iter()
+        .next()
+}
+
+pub fn parse_base(args: &ArgMatches) -> Result<DateTime<FixedOffset>, CliError> {
+    let now = Local::now();
+    let now = now.with_timezone(&now.offset().fix());
+
+    let base = match args.value_of("base") {
+        Some(base) => base,
+        None => return Ok(now),
+    };
+
+    match time::parse_with_base(base, &now).map(|x| choose_one(x, None)) {
+        Ok(Some(permutation)) => Ok(permutation.datetime),
+        _ => {
+            error!("Unable to understand base time `{}`", base);
+            Err(CliError::new("Unknown format", 1))
+        }
+    }
+}
+
+fn parse_permutations(
+    input: &str,
+    base: &DateTime<FixedOffset>,
+    holidays: &Calendar,
+    args: &ArgMatches,
+) -> Result<Vec<Permutation>, CliError> {
+    match time::parse_with_options(input, base, parse_options(args, holidays)) {
+        Ok(date) => Ok(choose_permutations(date, preferred_rule(args))),
+        Err(reason) => {
+            error!("Unable to understand `{}`: {}", input, reason);
+            Err(CliError::new("Unknown format", 1))
+        }
+    }
+}
+
+/// Like `parse_permutations`, for commands that need a single time.
+fn parse_one(
+    input: &str,
+    base: &DateTime<FixedOffset>,
+    holidays: &Calendar,
+    args: &ArgMatches,
+) -> Result<Permutation, CliError> {
+    let parsed = match time::parse_with_options(input, base, parse_options(args, holidays)) {
+        Ok(parsed) => parsed,
+        Err(reason) => {
+            error!("Unable to understand `{}`: {}", input, reason);
+            return Err(CliError::new("Unknown format", 1));
+        }
+    };
+
+    match choose_one(parsed, preferred_rule(args)) {
+        Some(permutation) => Ok(permutation),
+        None => {
+            error!("Unable to understand `{}`: no reading of it exists", input);
+            Err(CliError::new("Unknown format", 1))
+        }
+    }
+}
+
+/// Read a single time against `base`, taking the `prefer` reading, or else the
+/// year, month, day one, when the input is ambiguous. A date on its own is
+/// read as the start of that day.
+pub fn parse_first(
+    input: &str,
+    base: &DateTime<FixedOffset>,
+    prefer: Option<DateRule>,
+    options: ParseOptions,
+) -> Option<DateTime<FixedOffset>> {
+    let parsed = time::parse_with_options(input, base, options).ok()?;
+    let date_only = is_date_only(&parsed);
+    let datetime = choose_one(parsed, prefer)?.datetime;
+
+    if date_only {
+        Some(datetime.date().and_hms(0, 0, 0))
+    } else {
+        Some(datetime)
+    }
+}
+
+/// A date without a time of day, which is written as just the date rather
+/// than at whatever the time is now.
+fn is_date_only(result: &TimeResult) -> bool {
+    match result {
+        TimeResult::String(string_time) => string_time.time().is_none(),
+        TimeResult::Epoch(_) => false,
+    }
+}
+
+/// Whether `datetime` is at the local zone's offset, so calendar math on it
+/// can follow the local clock changes instead of staying at that offset.
+fn is_local(datetime: &DateTime<FixedOffset>) -> bool {
+    datetime.with_timezone(&Local).offset().fix() == *datetime.offset()
+}
+
+pub fn parse_zone(zone: &str) -> Result<Tz, CliError> {
+    match Tz::from_str(zone) {
+        Ok(tz) => Ok(tz),
+        Err(_) => {
+            error!("Unknown time zone `{}`", zone);
+            Err(CliError::new("Unknown time zone", 1))
+        }
+    }
+}
+
+fn parse_templates(args: &ArgMatches) -> Result<Vec<OutputTemplate>, CliError> {
+    let formats = match args.values_of("format") {
+        Some(formats) => formats,
+        None => return Ok(Vec::new()),
+    };
+
+    formats
+        .map(|x| {
+            OutputTemplate::parse(x).map_err(|err| {
+                error!("{}", err);
+                CliError::new("Format error", 2)
+            })
+        })
+        .collect()
+}
+
+/// Print times worked out from the input, through `--format` when given.
+fn render_results(results: &[Permutation], args: &ArgMatches) -> Result<(), CliError> {
+    let templates = parse_templates(args)?;
+    if !templates.is_empty() {
+        render_templates(results, &templates)
+    } else if args.is_present("utc_only") {
+        for result in results {
+            println!("{}", result.datetime.with_timezone(&Utc));
+        }
+        Ok(())
+    } else {
+        render_permutations(
+            results,
+            &zones::display_zones(args)?,
+            args.is_present("explain"),
+        )
+    }
+}
+
+/// Print each permutation once per `--format`, and nothing else.
+fn render_templates(
+    permutations: &[Permutation],
+    templates: &[OutputTemplate],
+) -> Result<(), CliError> {
+    for permutation in permutations {
+        for template in templates {
+            println!(
+                "{}",
+                template.render(&permutation.datetime, permutation.zone)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn render_records(
+    permutations: Vec<Permutation>,
+    format: &str,
+    zones: &[Tz],
+) -> Result<(), CliError> {
+    let records: Vec<output::TimeRecord> = permutations
+        .iter()
+        .map(|x| output::TimeRecord::new(x, zones))
+        .collect();
+
+    let output = match format.trim().to_lowercase().as_str() {
+        "json" => records.to_json(),
+        "yaml" => records.to_yaml(),
+        "tsv" => records.to_tsv(),
+        _ => {
+            error!("Unable to format to {}", format);
+            return Err(CliError::new("Format error", 2));
+        }
     };
 
+    println!("{}", output);
+    Ok(())
+}
+
+fn render_permutations(
+    permutations: &[Permutation],
+    zones: &[Tz],
+    explain: bool,
+) -> Result<(), CliError> {
+    if explain && permutations.len() > 1 {
+        println!(
+            "The input can be read {} ways\n",
+            format!("{}", permutations.len()).bold()
+        );
+    }
+
     let mut first = true;
-    for datetime in datetimes {
+    for permutation in permutations {
+        let datetime = permutation.datetime;
         if !first {
             println!();
         }
         first = false;
 
         println!("Understood the date was {}", format!("{}", datetime).bold());
-        println!(
-            "  ├── Date 'human': {}",
-            format!("{}", datetime.format("%b %e %T %Y")).bold()
-        );
-        println!(
-            "  ├── Date in M/D/Y: {}",
-            format!(
-                "{}/{}/{}",
-                datetime.month(),
-                datetime.day(),
-                datetime.year()
-            ).bold()
-        );
-        println!(
-            "  ├── Date in YMD: {}",
-            format!(
-                "{}{:02}{:02}",
-                datetime.year(),
-                datetime.month(),
-                datetime.day()
-            ).bold()
-        );
-        println!(
-            "  ├── Day in year: {}",
-            format!("{}", datetime.ordinal()).bold()
-        );
-        println!(
-            "  ├── ISO week {}",
-            format!(
-                "{}-{}",
-                datetime.iso_week().year(),
-                datetime.iso_week().week()
-            ).bold()
-        );
-        println!(
-            "  ├── Day of week: {}",
-            format!("{:?}", datetime.weekday()).bold()
-        );
-        println!(
-            "  └── Time: {}",
-            format!("{} {}", datetime.time(), datetime.timezone()).bold()
-        );
-        println!(
-            "    ├── Unix epoch(s):  {}",
-            format!("{}", datetime.timestamp()).bold()
-        );
-        println!(
-            "    ├── Unix epoch(ms): {}",
-            format!("{}", datetime.timestamp_millis()).bold()
-        );
-        println!(
-            "    ├── Unix epoch(ns): {}",
-            format!("{}", datetime.timestamp_nanos()).bold()
-        );
-        println!(
-            "    ├── In UTC: {}",
-            format!("{}", datetime.with_timezone(&chrono::Utc)).bold()
-        );
-        println!(
-            "    ├── In Eastern: {}",
-            format!("{}", datetime.with_timezone(&chrono_tz::US::Eastern)).bold()
-        );
-        println!(
-            "    ├── In Central: {}",
-            format!("{}", datetime.with_timezone(&chrono_tz::US::Central)).bold()
-        );
-        println!(
-            "    ├── In Mountain: {}",
-            format!("{}", datetime.with_timezone(&chrono_tz::US::Mountain)).bold()
-        );
-        println!(
-            "    ├── In Arazona: {}",
-            format!("{}", datetime.with_timezone(&chrono_tz::US::Arizona)).bold()
-        );
-        println!(
-            "    └── In Pacific: {}",
-            format!("{}", datetime.with_timezone(&chrono_tz::US::Pacific)).bold()
-        );
+        if explain {
+            println!("  ├── Read as: {}", permutation.interpretation.bold());
+        }
+        render_details(&datetime, permutation.zone, zones);
     }
 
     Ok(())
 }
 
-fn render_utc(input: TimeResult) -> Result<(), CliError> {
-    let datetime = match input {
-        TimeResult::Epoch(epoch) => epoch.to_utc_date_time(),
-        TimeResult::String(string_format) => string_format.to_utc_date_time(),
+/// Everything about one time after the line saying what it is: the date in
+/// a few layouts, epochs, and the time in UTC and each of `zones`.
+fn render_details(datetime: &DateTime<FixedOffset>, zone: Option<Tz>, zones: &[Tz]) {
+    println!(
+        "  ├── Date 'human': {}",
+        format!("{}", datetime.format("%b %e %T %Y")).bold()
+    );
+    println!(
+        "  ├── Date in M/D/Y: {}",
+        format!(
+            "{}/{}/{}",
+            datetime.month(),
+            datetime.day(),
+            datetime.year()
+        ).bold()
+    );
+    println!("  ├── Date in YMD: {}", output::date_ymd(datetime).bold());
+    println!(
+        "  ├── Day in year: {}",
+        format!("{}", datetime.ordinal()).bold()
+    );
+    println!(
+        "  ├── ISO week {}",
+        format!(
+            "{}-{}",
+            datetime.iso_week().year(),
+            datetime.iso_week().week()
+        )
+        .bold()
+    );
+    println!(
+        "  ├── Day of week: {}",
+        format!("{:?}", datetime.weekday()).bold()
+    );
+    let time = match zone {
+        Some(zone) => format!(
+            "{} {} ({} {})",
+            datetime.time(),
+            datetime.timezone(),
+            zone.name(),
+            datetime.with_timezone(&zone).format("%Z")
+        ),
+        None => format!("{} {}", datetime.time(), datetime.timezone()),
     };
+    println!("  └── Time: {}", time.bold());
+    println!(
+        "    ├── Unix epoch(s):  {}",
+        format!("{}", datetime.timestamp()).bold()
+    );
+    println!(
+        "    ├── Unix epoch(ms): {}",
+        format!("{}", datetime.timestamp_millis()).bold()
+    );
+    println!(
+        "    ├── Unix epoch(ns): {}",
+        output::epoch_nanos(datetime)
+            .map(|x| format!("{}", x))
+            .unwrap_or_else(|| s!("out of range"))
+            .bold()
+    );
+    println!(
+        "    ├── In UTC: {}",
+        format!("{}", datetime.with_timezone(&chrono::Utc)).bold()
+    );
+    for (index, zone) in zones.iter().enumerate() {
+        let branch = if index + 1 == zones.len() {
+            "└──"
+        } else {
+            "├──"
+        };
+        let in_zone = datetime.with_timezone(zone);
 
-    println!("{}", datetime);
-    Ok(())
+        println!(
+            "    {} In {}: {}",
+            branch,
+            zone.name(),
+            format!("{} (UTC{})", in_zone, in_zone.offset().fix()).bold()
+        );
+    }
+}
+
+fn render_utc(input: TimeResult, prefer: Option<DateRule>) -> Result<(), CliError> {
+    if prefer.is_some() {
+        for permutation in choose_permutations(input, prefer) {
+            println!("{}", permutation.datetime.with_timezone(&Utc));
+        }
+        return Ok(());
+    }
+
+    match input.to_utc_date_time() {
+        Some(datetime) => {
+            println!("{}", datetime);
+            Ok(())
+        }
+        None => {
+            error!("Unable to place the input in UTC");
+            Err(CliError::new("Unknown format", 1))
+        }
+    }
 }
@@ -0,0 +1,287 @@
+use chrono::prelude::*;
+use chrono_tz::Tz;
+use json::JsonValue;
+use toolkit::time::Permutation;
+
+pub trait ToJson {
+    fn to_json(&self) -> String;
+}
+
+pub trait ToYaml {
+    fn to_yaml(&self) -> String;
+}
+
+pub trait ToTsv {
+    fn to_tsv(&self) -> String;
+}
+
+/// How a time is written when it's a single value, like a line of `--batch`
+/// or a field rewritten by `json retime`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ValueFormat {
+    Rfc3339,
+    Utc,
+    Epoch,
+    EpochMillis,
+    EpochNanos,
+    /// The date as `20181204`.
+    Ymd,
+    /// The ISO week as `2018-W49`.
+    IsoWeek,
+}
+
+impl ValueFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "rfc3339" | "iso" => Some(ValueFormat::Rfc3339),
+            "utc" => Some(ValueFormat::Utc),
+            "epoch" => Some(ValueFormat::Epoch),
+            "epoch-ms" => Some(ValueFormat::EpochMillis),
+            "epoch-ns" => Some(ValueFormat::EpochNanos),
+            "ymd" => Some(ValueFormat::Ymd),
+            "iso-week" => Some(ValueFormat::IsoWeek),
+            _ => None,
+        }
+    }
+
+    pub fn render(self, datetime: &DateTime<FixedOffset>) -> String {
+        match self {
+            ValueFormat::Rfc3339 => datetime.to_rfc3339(),
+            ValueFormat::Utc => datetime.with_timezone(&Utc).to_rfc3339(),
+            ValueFormat::Epoch => format!("{}", datetime.timestamp()),
+            ValueFormat::EpochMillis => format!("{}", datetime.timestamp_millis()),
+            ValueFormat::EpochNanos => match epoch_nanos(datetime) {
+                Some(nanos) => format!("{}", nanos),
+                None => s!("out of range"),
+            },
+            ValueFormat::Ymd => date_ymd(datetime),
+            ValueFormat::IsoWeek => iso_week(datetime),
+        }
+    }
+}
+
+pub fn date_ymd<Tz: TimeZone>(datetime: &DateTime<Tz>) -> String {
+    format!(
+        "{}{:02}{:02}",
+        datetime.year(),
+        datetime.month(),
+        datetime.day()
+    )
+}
+
+pub fn iso_week<Tz: TimeZone>(datetime: &DateTime<Tz>) -> String {
+    format!(
+        "{}-W{:02}",
+        datetime.iso_week().year(),
+        datetime.iso_week().week()
+    )
+}
+
+/// Nanoseconds since the epoch, or `None` when that doesn't fit in an `i64`
+/// (before 1677 or after 2262).
+pub fn epoch_nanos<Tz: TimeZone>(datetime: &DateTime<Tz>) -> Option<i64> {
+    datetime
+        .timestamp()
+        .checked_mul(1_000_000_000)?
+        .checked_add(i64::from(datetime.timestamp_subsec_nanos()))
+}
+
+/// Everything `render_permutations` shows for one permutation, in a form that
+/// can be handed to other tools.
+#[derive(Serialize, Debug)]
+pub struct TimeRecord {
+    pub interpretation: String,
+    pub datetime: String,
+    pub utc: String,
+    pub epoch_seconds: i64,
+    pub epoch_millis: i64,
+    pub epoch_micros: i64,
+    pub epoch_nanos: Option<i64>,
+    pub date_human: String,
+    pub date_mdy: String,
+    pub date_ymd: String,
+    pub day_of_year: u32,
+    pub iso_week: String,
+    pub weekday: String,
+    pub time: String,
+    pub offset: String,
+    pub zones: Vec<ZoneRecord>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ZoneRecord {
+    pub zone: String,
+    pub abbreviation: String,
+    pub utc_offset: String,
+    pub datetime: String,
+}
+
+impl TimeRecord {
+    pub fn new(permutation: &Permutation, zones: &[Tz]) -> Self {
+        let datetime = permutation.datetime;
+
+        let zones = zones
+            .iter()
+            .map(|zone| {
+                let in_zone = datetime.with_timezone(zone);
+                ZoneRecord {
+                    zone: s!(zone.name()),
+                    abbreviation: format!("{}", in_zone.offset()),
+                    utc_offset: format!("{}", in_zone.offset().fix()),
+                    datetime: in_zone.to_rfc3339(),
+                }
+            })
+            .collect();
+
+        TimeRecord {
+            interpretation: permutation.interpretation.clone(),
+            datetime: datetime.to_rfc3339(),
+            utc: datetime.with_timezone(&Utc).to_rfc3339(),
+            epoch_seconds: datetime.timestamp(),
+            epoch_millis: datetime.timestamp_millis(),
+            epoch_micros: datetime.timestamp() * 1_000_000
+                + i64::from(datetime.timestamp_subsec_micros()),
+            epoch_nanos: epoch_nanos(&datetime),
+            date_human: format!("{}", datetime.format("%b %e %T %Y")),
+            date_mdy: format!(
+                "{}/{}/{}",
+                datetime.month(),
+                datetime.day(),
+                datetime.year()
+            ),
+            date_ymd: date_ymd(&datetime),
+            day_of_year: datetime.ordinal(),
+            iso_week: iso_week(&datetime),
+            weekday: format!("{:?}", datetime.weekday()),
+            time: format!("{}", datetime.time()),
+            offset: format!("{}", datetime.offset()),
+            zones,
+        }
+    }
+}
+
+impl ToJson for [TimeRecord] {
+    fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+impl ToYaml for [TimeRecord] {
+    fn to_yaml(&self) -> String {
+        let mut lines = Vec::new();
+        for record in self {
+            yaml_entries(&record.to_ordered(), "", "- ", &mut lines);
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl ToTsv for [TimeRecord] {
+    fn to_tsv(&self) -> String {
+        let records: Vec<JsonValue> = self.iter().map(TimeRecord::to_ordered).collect();
+
+        let mut header: Vec<String> = Vec::new();
+        if let (Some(record), Some(ordered)) = (self.first(), records.first()) {
+            header.extend(
+                ordered
+                    .entries()
+                    .filter(|(_, value)| !value.is_array())
+                    .map(|(name, _)| s!(name)),
+            );
+            header.extend(record.zones.iter().map(|x| s!(x.zone)));
+        }
+
+        let mut lines = vec![header.join("\t")];
+        for (record, ordered) in self.iter().zip(&records) {
+            let mut row: Vec<String> = ordered
+                .entries()
+                .filter(|(_, value)| !value.is_array())
+                .map(|(_, value)| match value.as_str() {
+                    Some(value) => s!(value),
+                    None => value.dump(),
+                })
+                .collect();
+            row.extend(record.zones.iter().map(|x| x.datetime.clone()));
+
+            lines.push(row.join("\t"));
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl TimeRecord {
+    /// The record as serde writes it, with its fields in the order they're
+    /// declared. serde_json's own `Value` would sort them by name.
+    fn to_ordered(&self) -> JsonValue {
+        json::parse(&serde_json::to_string(self).unwrap()).unwrap()
+    }
+}
+
+/// Write each field of `object` on its own line, starting the first one with
+/// `prefix` (`- ` for a list item). Lists and objects go on the lines after
+/// their name, indented.
+fn yaml_entries(object: &JsonValue, indent: &str, prefix: &str, lines: &mut Vec<String>) {
+    let inner = format!("{}{}", indent, " ".repeat(prefix.len()));
+    let mut prefix = format!("{}{}", indent, prefix);
+
+    for (name, value) in object.entries() {
+        if value.is_empty() || !(value.is_array() || value.is_object()) {
+            lines.push(format!("{}{}: {}", prefix, name, value.dump()));
+        } else {
+            lines.push(format!("{}{}:", prefix, name));
+            let nested = format!("{}  ", inner);
+            if value.is_object() {
+                yaml_entries(value, &nested, "", lines);
+            }
+            for item in value.members() {
+                if item.is_object() {
+                    yaml_entries(item, &nested, "- ", lines);
+                } else {
+                    lines.push(format!("{}- {}", nested, item.dump()));
+                }
+            }
+        }
+        prefix = inner.clone();
+    }
+}
+
+#[test]
+fn record_covers_permutation() {
+    let permutation = Permutation {
+        datetime: FixedOffset::west(8 * 3600)
+            .ymd(2018, 12, 3)
+            .and_hms_milli(20, 20, 22, 205),
+        interpretation: s!("year/month/day"),
+        rule: None,
+        zone: None,
+    };
+
+    let record = TimeRecord::new(&permutation, &[chrono_tz::Asia::Kolkata]);
+    assert_eq!("2018-12-04T04:20:22.205+00:00", record.utc);
+    assert_eq!(1_543_897_222_205, record.epoch_millis);
+    assert_eq!(1_543_897_222_205_000, record.epoch_micros);
+    assert_eq!("2018-W49", record.iso_week);
+    assert_eq!(337, record.day_of_year);
+    assert_eq!("IST", record.zones[0].abbreviation);
+    assert_eq!("+05:30", record.zones[0].utc_offset);
+
+    let records = [record];
+    let tsv = records.to_tsv();
+    let lines: Vec<&str> = tsv.lines().collect();
+    assert_eq!(2, lines.len());
+    assert_eq!(lines[0].split('\t').count(), lines[1].split('\t').count());
+    assert!(lines[0].starts_with("interpretation\tdatetime\tutc\t"));
+    assert!(lines[0].ends_with("\toffset\tAsia/Kolkata"));
+
+    let yaml = clap::YamlLoader::load_from_str(&records.to_yaml()).unwrap();
+    let record = &yaml[0][0];
+    assert_eq!(Some("year/month/day"), record["interpretation"].as_str());
+    assert_eq!(
+        Some(1_543_897_222_205_000_000),
+        record["epoch_nanos"].as_i64()
+    );
+    assert_eq!(Some("Asia/Kolkata"), record["zones"][0]["zone"].as_str());
+    assert_eq!(Some("IST"), record["zones"][0]["abbreviation"].as_str());
+}
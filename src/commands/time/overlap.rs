@@ -0,0 +1,337 @@
+use chrono::naive::NaiveTime;
+use chrono::prelude::*;
+use chrono::Duration;
+use chrono_tz::Tz;
+use clap::ArgMatches;
+use colored::*;
+use toolkit::time::relative;
+
+use super::catalog::zone_status;
+use super::diff::{WORK_DAY_END, WORK_DAY_START};
+use crate::commands::CliError;
+
+/// How far ahead to point out a zone's clocks changing, since that moves the
+/// overlap for the next few weekly meetings.
+const UPCOMING_CHANGE_DAYS: i64 = 28;
+
+/// The hours someone works on weekdays, on their own wall clock.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct WorkingHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl Default for WorkingHours {
+    fn default() -> Self {
+        WorkingHours {
+            start: NaiveTime::from_hms(WORK_DAY_START, 0, 0),
+            end: NaiveTime::from_hms(WORK_DAY_END, 0, 0),
+        }
+    }
+}
+
+impl WorkingHours {
+    /// Parse `9-17` or `08:30-17:30`.
+    fn parse(input: &str) -> Option<Self> {
+        let mut parts = input.trim().splitn(2, '-');
+        let start = parse_hour(parts.next()?)?;
+        let end = parse_hour(parts.next()?)?;
+
+        if end <= start {
+            return None;
+        }
+
+        Some(WorkingHours { start, end })
+    }
+
+    fn contains(&self, local: &NaiveDateTime) -> bool {
+        match local.weekday() {
+            Weekday::Sat | Weekday::Sun => false,
+            _ => local.time() >= self.start && local.time() < self.end,
+        }
+    }
+}
+
+fn parse_hour(input: &str) -> Option<NaiveTime> {
+    let input = input.trim();
+    match input.find(':') {
+        Some(index) => NaiveTime::from_hms_opt(
+            input[..index].parse().ok()?,
+            input[index + 1..].parse().ok()?,
+            0,
+        ),
+        None => NaiveTime::from_hms_opt(input.parse().ok()?, 0, 0),
+    }
+}
+
+/// One hour of the day, and whether each zone is working when it starts.
+#[derive(Debug, PartialEq)]
+struct OverlapRow {
+    start: DateTime<Utc>,
+    working: Vec<bool>,
+}
+
+impl OverlapRow {
+    fn everyone(&self) -> bool {
+        self.working.iter().all(|x| *x)
+    }
+}
+
+pub fn do_time_overlap_command(args: &ArgMatches) -> Result<(), CliError> {
+    let base = super::parse_base(args)?;
+    let zones = super::zones::display_zones(args)?;
+    let hours = parse_hours(args, &zones)?;
+    let reference = zones[0];
+
+    let date = match args.values_of("DATE") {
+        Some(date) => {
+            let input: Vec<&str> = date.collect();
+            let input = input.join(" ");
+            let holidays = super::business::holidays_for(&input, args)?;
+            super::parse_one(&input, &base, &holidays, args)?
+                .datetime
+                .naive_local()
+                .date()
+        }
+        None => base.with_timezone(&reference).naive_local().date(),
+    };
+
+    let rows = build_rows(date, &zones, &hours);
+    render_grid(date, &zones, &hours, &rows);
+    render_notes(&zones, &rows);
+
+    Ok(())
+}
+
+/// `--hours` for each of `zones`.
+fn parse_hours(args: &ArgMatches, zones: &[Tz]) -> Result<Vec<WorkingHours>, CliError> {
+    let (everyone, by_zone) = read_hours(args)?;
+    Ok(zones
+        .iter()
+        .map(|zone| hours_in(everyone, &by_zone, Some(*zone)))
+        .collect())
+}
+
+/// `--hours` for times in `zone`, or everyone's when it isn't given its own.
+pub fn parse_hours_in(args: &ArgMatches, zone: Option<Tz>) -> Result<WorkingHours, CliError> {
+    let (everyone, by_zone) = read_hours(args)?;
+    Ok(hours_in(everyone, &by_zone, zone))
+}
+
+fn hours_in(
+    everyone: WorkingHours,
+    by_zone: &[(Tz, WorkingHours)],
+    zone: Option<Tz>,
+) -> WorkingHours {
+    by_zone
+        .iter()
+        .find(|(x, _)| Some(*x) == zone)
+        .map_or(everyone, |(_, hours)| *hours)
+}
+
+/// `--hours` is either `9-17` for everyone, or `Asia/Kolkata=10-19` for one
+/// zone. Returns everyone's hours and the ones given for a zone.
+fn read_hours(args: &ArgMatches) -> Result<(WorkingHours, Vec<(Tz, WorkingHours)>), CliError> {
+    let mut everyone = WorkingHours::default();
+    let mut by_zone: Vec<(Tz, WorkingHours)> = Vec::new();
+
+    for value in args.values_of("hours").into_iter().flatten() {
+        let (zone, hours) = match value.find('=') {
+            Some(index) => (
+                Some(super::parse_zone(&value[..index])?),
+                &value[index + 1..],
+            ),
+            None => (None, value),
+        };
+
+        let hours = match WorkingHours::parse(hours) {
+            Some(hours) => hours,
+            None => {
+                error!("Unable to understand working hours `{}`", value);
+                return Err(CliError::new("Format error", 2));
+            }
+        };
+
+        match zone {
+            Some(zone) => by_zone.push((zone, hours)),
+            None => everyone = hours,
+        }
+    }
+
+    Ok((everyone, by_zone))
+}
+
+/// Every hour of `date` in the first zone. That's 23 or 25 hours when its
+/// clocks change that day.
+fn build_rows(date: NaiveDate, zones: &[Tz], hours: &[WorkingHours]) -> Vec<OverlapRow> {
+    let reference = zones[0];
+    let midnight = |date: NaiveDate| {
+        relative::from_local(&reference, &date.and_hms(0, 0, 0))
+            .unwrap()
+            .with_timezone(&Utc)
+    };
+
+    let end = midnight(date.succ());
+    let mut start = midnight(date);
+    let mut rows = Vec::new();
+    while start < end {
+        rows.push(OverlapRow {
+            start,
+            working: zones
+                .iter()
+                .zip(hours)
+                .map(|(zone, hours)| hours.contains(&start.with_timezone(zone).naive_local()))
+                .collect(),
+        });
+        start = start + Duration::hours(1);
+    }
+
+    rows
+}
+
+fn render_grid(date: NaiveDate, zones: &[Tz], hours: &[WorkingHours], rows: &[OverlapRow]) {
+    println!(
+        "Working hours on {} in {}\n",
+        format!("{}", date.format("%a %Y-%m-%d")).bold(),
+        zones[0].name().bold()
+    );
+
+    let widths: Vec<usize> = zones.iter().map(|x| x.name().len().max(15)).collect();
+
+    let mut header = format!("{:<5}", "UTC");
+    let mut working = format!("{:<5}", "");
+    for ((zone, hours), width) in zones.iter().zip(hours).zip(&widths) {
+        header.push_str(&format!("  {:<width$}", zone.name(), width = *width));
+        let span = format!(
+            "{}-{}",
+            hours.start.format("%H:%M"),
+            hours.end.format("%H:%M")
+        );
+        working.push_str(&format!("  {:<width$}", span, width = *width));
+    }
+    println!("{}", header.bold());
+    println!("{}", working.dimmed());
+
+    for row in rows {
+        let mut line = format!("{}", row.start.format("%H:%M"));
+        for ((zone, working), width) in zones.iter().zip(&row.working).zip(&widths) {
+            let local = format!("{}", row.start.with_timezone(zone).format("%a %H:%M %Z"));
+            let cell = format!("  {:<width$}", local, width = *width);
+            if *working {
+                line.push_str(&format!("{}", cell.bold()));
+            } else {
+                line.push_str(&format!("{}", cell.dimmed()));
+            }
+        }
+
+        if row.everyone() {
+            println!("{}  {}", line, "◀ everyone".green().bold());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+fn render_notes(zones: &[Tz], rows: &[OverlapRow]) {
+    let reference = zones[0];
+    let overlap = rows.iter().filter(|x| x.everyone()).count();
+
+    println!();
+    if overlap == 0 {
+        println!("{}", "No hour is inside everyone's working hours".bold());
+    } else {
+        let first = rows.iter().find(|x| x.everyone()).unwrap().start;
+        println!(
+            "Everyone is working for {} starting {}",
+            format!("{} hour(s)", overlap).bold(),
+            format!("{}", first.with_timezone(&reference).format("%H:%M %Z")).bold()
+        );
+    }
+
+    let start = rows[0].start;
+    let end = rows[rows.len() - 1].start + Duration::hours(1);
+    for (index, zone) in zones.iter().enumerate() {
+        let branch = if index + 1 == zones.len() {
+            "└──"
+        } else {
+            "├──"
+        };
+        let status = zone_status(*zone, &start);
+
+        let change = match status.next_change {
+            Some(at) if at < end => format!(
+                ", the clocks change at {} that day",
+                at.with_timezone(zone).format("%H:%M %Z")
+            ),
+            Some(at) if at < start + Duration::days(UPCOMING_CHANGE_DAYS) => format!(
+                ", changing to UTC{} on {}, which moves the overlap",
+                at.with_timezone(zone).offset().fix(),
+                at.with_timezone(zone).format("%a %Y-%m-%d")
+            ),
+            _ => s!(""),
+        };
+
+        println!(
+            "  {} {} is on {}{}",
+            branch,
+            zone.name(),
+            format!(
+                "UTC{} {} ({})",
+                status.offset,
+                status.abbreviation,
+                if status.dst {
+                    "daylight saving"
+                } else {
+                    "standard time"
+                }
+            )
+            .bold(),
+            change
+        );
+    }
+}
+
+#[test]
+fn overlap_rows() {
+    assert_eq!(Some(WorkingHours::default()), WorkingHours::parse("9-17"));
+    assert_eq!(
+        Some(WorkingHours {
+            start: NaiveTime::from_hms(8, 30, 0),
+            end: NaiveTime::from_hms(17, 30, 0),
+        }),
+        WorkingHours::parse("08:30-17:30")
+    );
+    assert_eq!(None, WorkingHours::parse("17-9"));
+    assert_eq!(None, WorkingHours::parse("nine-five"));
+
+    let zones = [chrono_tz::America::New_York, chrono_tz::Europe::Berlin];
+    let hours = [WorkingHours::default(), WorkingHours::default()];
+    let rows = build_rows(NaiveDate::from_ymd(2018, 12, 4), &zones, &hours);
+    assert_eq!(24, rows.len());
+    assert_eq!(Utc.ymd(2018, 12, 4).and_hms(5, 0, 0), rows[0].start);
+    let overlap: Vec<DateTime<Utc>> = rows
+        .iter()
+        .filter(|x| x.everyone())
+        .map(|x| x.start)
+        .collect();
+    assert_eq!(
+        vec![
+            Utc.ymd(2018, 12, 4).and_hms(14, 0, 0),
+            Utc.ymd(2018, 12, 4).and_hms(15, 0, 0)
+        ],
+        overlap
+    );
+
+    // Berlin starting an hour early gives one more hour together.
+    let hours = [
+        WorkingHours::default(),
+        WorkingHours::parse("8-18").unwrap(),
+    ];
+    let rows = build_rows(NaiveDate::from_ymd(2018, 12, 4), &zones, &hours);
+    assert_eq!(3, rows.iter().filter(|x| x.everyone()).count());
+
+    // Nobody works on the weekend, and the day the clocks go back is longer.
+    let rows = build_rows(NaiveDate::from_ymd(2018, 11, 4), &zones, &hours);
+    assert_eq!(25, rows.len());
+    assert_eq!(0, rows.iter().filter(|x| x.everyone()).count());
+}
@@ -0,0 +1,361 @@
+use clap::ArgMatches;
+use colored::*;
+use toolkit::time::{self, Calendar, DateRule, EpochUnit, Field};
+
+use crate::commands::CliError;
+
+/// What a dialect is missing when it has no spelling for `field`.
+fn describe(field: Field) -> &'static str {
+    match field {
+        Field::DaySpacePadded => "days padded with a space",
+        Field::DaySuffix => "day suffixes like 4th",
+        Field::DayOfYear => "the day of the year",
+        Field::WeekYear | Field::Week | Field::WeekdayNumber => "ISO week dates",
+        Field::HourUnpadded => "24 hour clock hours without padding",
+        Field::Fraction(_) => "fractions of a second this long",
+        Field::OffsetHours => "offsets without minutes",
+        Field::ZoneAbbr => "zone abbreviations",
+        Field::ZoneId => "zone names like America/New_York",
+        Field::Epoch(_) => "epochs in this unit",
+        _ => "this",
+    }
+}
+
+/// A language whose date patterns can be written out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Dialect {
+    Strftime,
+    Java,
+    Go,
+    Python,
+    Moment,
+}
+
+const DIALECTS: [Dialect; 5] = [
+    Dialect::Strftime,
+    Dialect::Java,
+    Dialect::Go,
+    Dialect::Python,
+    Dialect::Moment,
+];
+
+impl Dialect {
+    fn name(self) -> &'static str {
+        match self {
+            Dialect::Strftime => "strftime/chrono",
+            Dialect::Java => "Java DateTimeFormatter",
+            Dialect::Go => "Go layout",
+            Dialect::Python => "Python strptime",
+            Dialect::Moment => "Moment.js",
+        }
+    }
+
+    /// How `field` is spelled, if it can be.
+    fn field(self, field: Field) -> Option<String> {
+        use self::Dialect::*;
+
+        let spelling = match (self, field) {
+            (Strftime, Field::Year) | (Python, Field::Year) => "%Y",
+            (Java, Field::Year) => "yyyy",
+            (Go, Field::Year) => "2006",
+            (Moment, Field::Year) => "YYYY",
+            (Strftime, Field::ShortYear) | (Python, Field::ShortYear) => "%y",
+            (Java, Field::ShortYear) => "yy",
+            (Go, Field::ShortYear) => "06",
+            (Moment, Field::ShortYear) => "YY",
+            (Strftime, Field::Month) | (Python, Field::Month) => "%m",
+            (Java, Field::Month) | (Moment, Field::Month) => "MM",
+            (Go, Field::Month) => "01",
+            (Strftime, Field::MonthUnpadded) => "%-m",
+            (Python, Field::MonthUnpadded) => "%m",
+            (Java, Field::MonthUnpadded) | (Moment, Field::MonthUnpadded) => "M",
+            (Go, Field::MonthUnpadded) => "1",
+            (Strftime, Field::MonthAbbr) | (Python, Field::MonthAbbr) => "%b",
+            (Java, Field::MonthAbbr) | (Moment, Field::MonthAbbr) => "MMM",
+            (Go, Field::MonthAbbr) => "Jan",
+            (Strftime, Field::MonthName) | (Python, Field::MonthName) => "%B",
+            (Java, Field::MonthName) | (Moment, Field::MonthName) => "MMMM",
+            (Go, Field::MonthName) => "January",
+            (Strftime, Field::Day) | (Python, Field::Day) => "%d",
+            (Java, Field::Day) => "dd",
+            (Go, Field::Day) => "02",
+            (Moment, Field::Day) => "DD",
+            (Strftime, Field::DayUnpadded) => "%-d",
+            (Python, Field::DayUnpadded) => "%d",
+            (Java, Field::DayUnpadded) => "d",
+            (Go, Field::DayUnpadded) => "2",
+            (Moment, Field::DayUnpadded) => "D",
+            (Strftime, Field::DaySpacePadded) => "%e",
+            (Python, Field::DaySpacePadded) => " %d",
+            (Java, Field::DaySpacePadded) => "ppd",
+            (Go, Field::DaySpacePadded) => "_2",
+            (Moment, Field::DaySpacePadded) => " D",
+            // Moment's `Do` is the day with its suffix, and the suffix always
+            // follows the day's `D`.
+            (Moment, Field::DaySuffix) => "o",
+            (Strftime, Field::DayOfYear) | (Python, Field::DayOfYear) => "%j",
+            (Java, Field::DayOfYear) => "DDD",
+            (Go, Field::DayOfYear) => "002",
+            (Moment, Field::DayOfYear) => "DDDD",
+            (Strftime, Field::WeekYear) | (Python, Field::WeekYear) => "%G",
+            (Moment, Field::WeekYear) => "GGGG",
+            (Strftime, Field::Week) | (Python, Field::Week) => "%V",
+            (Moment, Field::Week) => "WW",
+            (Strftime, Field::WeekdayNumber) | (Python, Field::WeekdayNumber) => "%u",
+            (Moment, Field::WeekdayNumber) => "E",
+            (Strftime, Field::WeekdayAbbr) | (Python, Field::WeekdayAbbr) => "%a",
+            (Java, Field::WeekdayAbbr) => "EEE",
+            (Go, Field::WeekdayAbbr) => "Mon",
+            (Moment, Field::WeekdayAbbr) => "ddd",
+            (Strftime, Field::WeekdayName) | (Python, Field::WeekdayName) => "%A",
+            (Java, Field::WeekdayName) => "EEEE",
+            (Go, Field::WeekdayName) => "Monday",
+            (Moment, Field::WeekdayName) => "dddd",
+            (Strftime, Field::Hour) | (Python, Field::Hour) => "%H",
+            (Java, Field::Hour) | (Moment, Field::Hour) => "HH",
+            (Go, Field::Hour) => "15",
+            (Strftime, Field::HourUnpadded) => "%-H",
+            (Python, Field::HourUnpadded) => "%H",
+            (Java, Field::HourUnpadded) | (Moment, Field::HourUnpadded) => "H",
+            (Strftime, Field::Hour12) | (Python, Field::Hour12) => "%I",
+            (Java, Field::Hour12) | (Moment, Field::Hour12) => "hh",
+            (Go, Field::Hour12) => "03",
+            (Strftime, Field::Hour12Unpadded) => "%-I",
+            (Python, Field::Hour12Unpadded) => "%I",
+            (Java, Field::Hour12Unpadded) | (Moment, Field::Hour12Unpadded) => "h",
+            (Go, Field::Hour12Unpadded) => "3",
+            (Strftime, Field::Minute) | (Python, Field::Minute) => "%M",
+            (Java, Field::Minute) | (Moment, Field::Minute) => "mm",
+            (Go, Field::Minute) => "04",
+            (Strftime, Field::Second) | (Python, Field::Second) => "%S",
+            (Java, Field::Second) | (Moment, Field::Second) => "ss",
+            (Go, Field::Second) => "05",
+            (Strftime, Field::Fraction(3)) => "%3f",
+            (Strftime, Field::Fraction(6)) => "%6f",
+            (Strftime, Field::Fraction(9)) => "%9f",
+            (Python, Field::Fraction(digits)) if digits <= 6 => "%f",
+            (Java, Field::Fraction(digits)) | (Moment, Field::Fraction(digits)) if digits <= 9 => {
+                return Some("S".repeat(digits));
+            }
+            (Go, Field::Fraction(digits)) if digits <= 9 => return Some("0".repeat(digits)),
+            (Strftime, Field::AmPm) | (Python, Field::AmPm) => "%p",
+            (Java, Field::AmPm) => "a",
+            (Go, Field::AmPm) => "PM",
+            (Moment, Field::AmPm) => "A",
+            (Strftime, Field::Offset) | (Python, Field::Offset) => "%z",
+            (Java, Field::Offset) => "Z",
+            (Go, Field::Offset) => "-0700",
+            (Moment, Field::Offset) => "ZZ",
+            (Strftime, Field::OffsetColon) => "%:z",
+            (Python, Field::OffsetColon) => "%z",
+            (Java, Field::OffsetColon) => "xxx",
+            (Go, Field::OffsetColon) => "-07:00",
+            (Moment, Field::OffsetColon) => "Z",
+            (Strftime, Field::OffsetHours) => "%#z",
+            (Java, Field::OffsetHours) => "x",
+            (Go, Field::OffsetHours) => "-07",
+            (Strftime, Field::Utc) => "Z",
+            (Python, Field::Utc) => "%z",
+            (Java, Field::Utc) => "XXX",
+            (Go, Field::Utc) => "Z07:00",
+            (Moment, Field::Utc) => "Z",
+            (Strftime, Field::ZoneAbbr) | (Python, Field::ZoneAbbr) => "%Z",
+            (Java, Field::ZoneAbbr) => "z",
+            (Go, Field::ZoneAbbr) => "MST",
+            (Java, Field::ZoneId) => "VV",
+            (Strftime, Field::Epoch(EpochUnit::Seconds)) => "%s",
+            (Moment, Field::Epoch(EpochUnit::Seconds)) => "X",
+            (Moment, Field::Epoch(EpochUnit::Milliseconds)) => "x",
+            _ => return None,
+        };
+
+        Some(s!(spelling))
+    }
+
+    /// Text that's matched as it is, escaped so it isn't read as a field.
+    fn literal(self, text: &str) -> String {
+        match self {
+            Dialect::Strftime | Dialect::Python => text.replace('%', "%%"),
+            Dialect::Go => s!(text),
+            Dialect::Java if text.chars().any(|x| x.is_ascii_alphabetic() || x == '\'') => {
+                format!("'{}'", text.replace('\'', "''"))
+            }
+            Dialect::Moment if text.chars().any(|x| x.is_ascii_alphabetic()) => {
+                format!("[{}]", text)
+            }
+            Dialect::Java | Dialect::Moment => s!(text),
+        }
+    }
+
+    /// The whole pattern for `input`, or the first field this dialect has no
+    /// way to write.
+    fn pattern(
+        self,
+        input: &str,
+        fields: &[(std::ops::Range<usize>, Field)],
+    ) -> Result<String, Field> {
+        let mut pattern = String::new();
+        let mut last = 0;
+        for (range, field) in fields {
+            if range.start > last {
+                pattern.push_str(&self.literal(&input[last..range.start]));
+            }
+            pattern.push_str(&self.field(*field).ok_or(*field)?);
+            last = range.end;
+        }
+        if last < input.len() {
+            pattern.push_str(&self.literal(&input[last..]));
+        }
+
+        Ok(pattern)
+    }
+}
+
+pub fn do_time_pattern_command(args: &ArgMatches) -> Result<(), CliError> {
+    let input: Vec<&str> = args.values_of("SAMPLE").unwrap().collect();
+    let sample = input.join(" ");
+    let sample = sample.trim();
+
+    let holidays = Calendar::default();
+    let options = super::parse_options(args, &holidays);
+    let base = super::parse_base(args)?;
+
+    // Every way the sample could be read, so `04/12/2018` gets a pattern for
+    // both day and month first.
+    let mut rules: Vec<Option<DateRule>> = Vec::new();
+    for permutation in super::parse_permutations(sample, &base, &holidays, args)? {
+        if !rules.contains(&permutation.rule) {
+            rules.push(permutation.rule);
+        }
+    }
+
+    let mut readings = Vec::new();
+    for rule in rules {
+        match time::find_fields(sample, rule, options) {
+            Ok(fields) => {
+                if !readings.iter().any(|(_, x)| *x == fields) {
+                    readings.push((rule, fields));
+                }
+            }
+            Err(reason) => {
+                error!("Unable to find a pattern: {}", reason);
+                return Err(CliError::new("Unknown format", 1));
+            }
+        }
+    }
+
+    if readings.len() > 1 {
+        println!(
+            "The input can be read {} ways\n",
+            format!("{}", readings.len()).bold()
+        );
+    }
+
+    for (index, (rule, fields)) in readings.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+
+        match rule {
+            Some(rule) if readings.len() > 1 => println!(
+                "Pattern of {} read as {}",
+                sample.bold(),
+                rule.describe().bold()
+            ),
+            _ => println!("Pattern of {}", sample.bold()),
+        }
+
+        for (position, dialect) in DIALECTS.iter().enumerate() {
+            let branch = if position + 1 == DIALECTS.len() {
+                "└──"
+            } else {
+                "├──"
+            };
+            let pattern = match dialect.pattern(sample, fields) {
+                Ok(pattern) => format!("{}", pattern.bold()),
+                Err(field) => format!("none, it can't write {}", describe(field)),
+            };
+            println!("  {} {}: {}", branch, dialect.name(), pattern);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+fn patterns_of(input: &str, rule: Option<DateRule>) -> Vec<String> {
+    let fields = time::find_fields(input, rule, time::ParseOptions::default()).unwrap();
+    DIALECTS
+        .iter()
+        .map(|x| x.pattern(input, &fields).unwrap_or_else(|_| s!("-")))
+        .collect()
+}
+
+#[test]
+fn patterns_in_each_dialect() {
+    assert_eq!(
+        vec![
+            "%d/%b/%Y:%H:%M:%S %z",
+            "dd/MMM/yyyy:HH:mm:ss Z",
+            "02/Jan/2006:15:04:05 -0700",
+            "%d/%b/%Y:%H:%M:%S %z",
+            "DD/MMM/YYYY:HH:mm:ss ZZ",
+        ],
+        patterns_of("04/Dec/2018:04:20:22 +0000", None)
+    );
+    assert_eq!(
+        vec![
+            "%Y-%m-%dT%H:%M:%S.%3fZ",
+            "yyyy-MM-dd'T'HH:mm:ss.SSSXXX",
+            "2006-01-02T15:04:05.000Z07:00",
+            "%Y-%m-%dT%H:%M:%S.%f%z",
+            "YYYY-MM-DD[T]HH:mm:ss.SSSZ",
+        ],
+        patterns_of("2018-12-04T04:20:22.205Z", None)
+    );
+    assert_eq!(
+        vec![
+            "%a %b %d %H:%M:%S %Z %Y",
+            "EEE MMM dd HH:mm:ss z yyyy",
+            "Mon Jan 02 15:04:05 MST 2006",
+            "%a %b %d %H:%M:%S %Z %Y",
+            "-",
+        ],
+        patterns_of("Tue Dec 04 04:20:22 PST 2018", None)
+    );
+    assert_eq!(
+        vec![
+            "%b %e %H:%M:%S",
+            "MMM ppd HH:mm:ss",
+            "Jan _2 15:04:05",
+            "%b  %d %H:%M:%S",
+            "MMM  D HH:mm:ss"
+        ],
+        patterns_of("Dec  4 04:20:22", None)
+    );
+    assert_eq!(
+        vec!["%s", "-", "-", "-", "X"],
+        patterns_of("1543897222", None)
+    );
+}
+
+#[test]
+fn patterns_follow_the_date_order() {
+    assert_eq!(
+        "%d/%m/%Y %-I:%M %p",
+        patterns_of("04/12/2018 4:20 pm", Some(DateRule::DayMonthYear))[0]
+    );
+    assert_eq!(
+        "%m/%d/%Y %-I:%M %p",
+        patterns_of("04/12/2018 4:20 pm", Some(DateRule::MonthDayYear))[0]
+    );
+    assert_eq!(
+        "%-m/%-d/%y",
+        patterns_of("4/5/18", Some(DateRule::MonthDayYear))[0]
+    );
+    assert_eq!(
+        "MMMM Do, YYYY h:mmA",
+        patterns_of("December 4th, 2018 4:20PM", None)[4]
+    );
+    assert!(time::find_fields("3 days ago", None, time::ParseOptions::default()).is_err());
+}
@@ -0,0 +1,267 @@
+use clap::ArgMatches;
+
+use chrono::prelude::*;
+use toolkit::time::relative::{self, Shift};
+use toolkit::time::Calendar;
+
+use super::output::ValueFormat;
+use crate::commands::CliError;
+
+/// Every step from a start time up to and including an end time. Each time
+/// is the start plus a whole number of steps, so monthly steps from the 31st
+/// land on the last day of shorter months without drifting to the 28th.
+struct Series<'a, Z: TimeZone> {
+    start: DateTime<Z>,
+    end: DateTime<Utc>,
+    shifts: &'a [Shift],
+    holidays: &'a Calendar,
+    forward: bool,
+    count: i64,
+}
+
+impl<'a, Z: TimeZone> Series<'a, Z> {
+    fn new(
+        start: DateTime<Z>,
+        end: DateTime<Utc>,
+        shifts: &'a [Shift],
+        holidays: &'a Calendar,
+    ) -> Result<Self, &'static str> {
+        let mut series = Series {
+            start,
+            end,
+            shifts,
+            holidays,
+            forward: true,
+            count: 0,
+        };
+
+        let start = series.start.with_timezone(&Utc);
+        let first = match series.nth_step(1) {
+            Some(first) => first.with_timezone(&Utc),
+            None => return Err("the first step is out of range"),
+        };
+        if first == start {
+            return Err("the step doesn't move the time");
+        }
+
+        series.forward = first > start;
+        if series.forward != (series.end >= start) && series.end != start {
+            return Err("the step moves away from the end");
+        }
+
+        Ok(series)
+    }
+
+    fn nth_step(&self, count: i64) -> Option<DateTime<Z>> {
+        let mut shifts = Vec::new();
+        for shift in self.shifts {
+            shifts.push(Shift::new(shift.amount.checked_mul(count)?, shift.unit));
+        }
+
+        relative::apply_shifts(self.start.clone(), &shifts, self.holidays)
+    }
+}
+
+impl<'a, Z: TimeZone> Iterator for Series<'a, Z> {
+    type Item = DateTime<FixedOffset>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.nth_step(self.count)?;
+        let at = current.with_timezone(&Utc);
+        if (self.forward && at > self.end) || (!self.forward && at < self.end) {
+            return None;
+        }
+
+        self.count += 1;
+        Some(current.with_timezone(&current.offset().fix()))
+    }
+}
+
+pub fn do_time_range_command(args: &ArgMatches) -> Result<(), CliError> {
+    let base = super::parse_base(args)?;
+
+    let step = args.value_of("step").unwrap_or("1d");
+    let shifts = match relative::parse_shifts(step) {
+        Some(shifts) => shifts,
+        None => {
+            error!("Unable to understand step `{}`", step);
+            return Err(CliError::new("Unknown duration", 1));
+        }
+    };
+
+    let format = match ValueFormat::from_name(args.value_of("range_format").unwrap_or("rfc3339")) {
+        Some(format) => format,
+        None => {
+            error!(
+                "Unable to format to {}",
+                args.value_of("range_format").unwrap()
+            );
+            return Err(CliError::new("Format error", 2));
+        }
+    };
+    let templates = super::parse_templates(args)?;
+    let (start, end) = (
+        args.value_of("START").unwrap(),
+        args.value_of("END").unwrap(),
+    );
+    let holidays = super::business::holidays_if(
+        super::business::counts_business_days(start)
+            || super::business::counts_business_days(end)
+            || super::business::steps_business_days(&shifts),
+        args,
+    )?;
+    let start = super::parse_one(start, &base, &holidays, args)?;
+    let end = super::parse_one(end, &base, &holidays, args)?
+        .datetime
+        .with_timezone(&Utc);
+
+    // Step on the wall clock of `--in-zone`, or else the zone the start was
+    // written in. A start with only an offset follows the local zone when it's
+    // on the local clock, and keeps its offset when it isn't.
+    let zone = match args.value_of("in_zone") {
+        Some(zone) => Some(super::parse_zone(zone)?),
+        None => start.zone,
+    };
+
+    let render = |datetime: DateTime<FixedOffset>| {
+        if templates.is_empty() {
+            println!("{}", format.render(&datetime));
+        }
+        for template in &templates {
+            println!("{}", template.render(&datetime, zone));
+        }
+    };
+
+    let result = match zone {
+        Some(zone) => Series::new(start.datetime.with_timezone(&zone), end, &shifts, &holidays)
+            .map(|series| series.for_each(&render)),
+        None if super::is_local(&start.datetime) => Series::new(
+            start.datetime.with_timezone(&Local),
+            end,
+            &shifts,
+            &holidays,
+        )
+        .map(|series| series.for_each(&render)),
+        None => Series::new(start.datetime, end, &shifts, &holidays)
+            .map(|series| series.for_each(&render)),
+    };
+
+    if let Err(reason) = result {
+        error!("Unable to step by `{}`: {}", step, reason);
+        return Err(CliError::new("Format error", 2));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+fn series_between(start: &str, end: &str, step: &str, zone: chrono_tz::Tz) -> Vec<String> {
+    let start = DateTime::parse_from_rfc3339(start)
+        .unwrap()
+        .with_timezone(&zone);
+    let end = DateTime::parse_from_rfc3339(end)
+        .unwrap()
+        .with_timezone(&Utc);
+    let shifts = relative::parse_shifts(step).unwrap();
+    let holidays = Calendar::default();
+
+    Series::new(start, end, &shifts, &holidays)
+        .unwrap()
+        .map(|x| x.to_rfc3339())
+        .collect()
+}
+
+#[test]
+fn series_takes_calendar_steps() {
+    use chrono_tz::Tz;
+
+    assert_eq!(
+        vec![
+            "2018-01-31T00:00:00+00:00",
+            "2018-02-28T00:00:00+00:00",
+            "2018-03-31T00:00:00+00:00",
+            "2018-04-30T00:00:00+00:00",
+        ],
+        series_between(
+            "2018-01-31T00:00:00Z",
+            "2018-05-01T00:00:00Z",
+            "1mo",
+            Tz::UTC
+        )
+    );
+
+    // Hours are elapsed time, so the repeated hour shows up twice, while days
+    // keep the wall clock time.
+    assert_eq!(
+        vec![
+            "2018-11-04T00:00:00-04:00",
+            "2018-11-04T01:00:00-04:00",
+            "2018-11-04T01:00:00-05:00",
+            "2018-11-04T02:00:00-05:00",
+        ],
+        series_between(
+            "2018-11-04T00:00:00-04:00",
+            "2018-11-04T02:00:00-05:00",
+            "1h",
+            Tz::America__New_York
+        )
+    );
+    assert_eq!(
+        vec![
+            "2018-11-03T09:00:00-04:00",
+            "2018-11-04T09:00:00-05:00",
+            "2018-11-05T09:00:00-05:00",
+        ],
+        series_between(
+            "2018-11-03T09:00:00-04:00",
+            "2018-11-05T09:00:00-05:00",
+            "1d",
+            Tz::America__New_York
+        )
+    );
+
+    assert_eq!(
+        vec!["2018-12-04T12:00:00+00:00", "2018-12-04T11:45:00+00:00"],
+        series_between(
+            "2018-12-04T12:00:00Z",
+            "2018-12-04T11:40:00Z",
+            "-15m",
+            Tz::UTC
+        )
+    );
+}
+
+#[test]
+fn series_rejects_steps_that_never_arrive() {
+    let start = Utc.ymd(2018, 12, 4).and_hms(0, 0, 0);
+    let end = Utc.ymd(2018, 12, 5).and_hms(0, 0, 0);
+    let holidays = Calendar::default();
+
+    let zero = relative::parse_shifts("0d").unwrap();
+    assert!(Series::new(start, end, &zero, &holidays).is_err());
+
+    let backwards = relative::parse_shifts("-1h").unwrap();
+    assert!(Series::new(start, end, &backwards, &holidays).is_err());
+
+    let once = relative::parse_shifts("1h").unwrap();
+    assert_eq!(
+        1,
+        Series::new(start, start, &once, &holidays).unwrap().count()
+    );
+}
+
+#[test]
+fn series_keeps_a_fixed_offset() {
+    let start = DateTime::parse_from_rfc3339("2018-12-01T00:00:00+09:00").unwrap();
+    let end = Utc.ymd(2018, 12, 2).and_hms(0, 0, 0);
+    let shifts = relative::parse_shifts("1d").unwrap();
+    let holidays = Calendar::default();
+
+    assert_eq!(
+        vec!["2018-12-01T00:00:00+09:00", "2018-12-02T00:00:00+09:00"],
+        Series::new(start, end, &shifts, &holidays)
+            .unwrap()
+            .map(|x| x.to_rfc3339())
+            .collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,135 @@
+use std::io::{BufRead, BufReader};
+use std::ops::Range;
+
+use chrono::prelude::*;
+use chrono_tz::Tz;
+use clap::ArgMatches;
+use toolkit::time::{self, Calendar, DateRule, TimeResult};
+
+use crate::commands::CliError;
+
+/// Where each time found in a line is written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Target {
+    Utc,
+    Local,
+    Zone(Tz),
+}
+
+impl Target {
+    fn render(self, datetime: &DateTime<FixedOffset>) -> String {
+        match self {
+            Target::Utc => datetime.with_timezone(&Utc).to_rfc3339(),
+            Target::Local => datetime.with_timezone(&Local).to_rfc3339(),
+            Target::Zone(zone) => datetime.with_timezone(&zone).to_rfc3339(),
+        }
+    }
+
+    fn zone(self) -> Option<Tz> {
+        match self {
+            Target::Zone(zone) => Some(zone),
+            _ => None,
+        }
+    }
+}
+
+pub fn do_time_scan_command(args: &ArgMatches) -> Result<(), CliError> {
+    let target = match (args.value_of("in_zone"), args.is_present("local")) {
+        (Some(zone), _) => Target::Zone(super::parse_zone(zone)?),
+        (None, true) => Target::Local,
+        (None, false) => Target::Utc,
+    };
+
+    let base = super::parse_base(args)?;
+    let prefer = super::preferred_rule(args);
+    let templates = super::parse_templates(args)?;
+    let replace = args.is_present("replace");
+
+    // Read the first time a line counts business days, then kept for the rest.
+    let mut holidays = None;
+    let input_paths: Vec<&str> = args.values_of("INPUT").unwrap().collect();
+    for input_path in input_paths {
+        let reader = match crate::commands::file::open_file(input_path) {
+            Ok(reader) => BufReader::new(reader),
+            Err(e) => {
+                error!("Unable to open {} because {}", input_path, e);
+                return Err(CliError::new("Unable to open file", 1));
+            }
+        };
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    error!("IO error: Line {}: {}", index + 1, err);
+                    return Err(CliError::new("IO error", 1));
+                }
+            };
+
+            if holidays.is_none() && super::business::counts_business_days(&line) {
+                holidays = Some(super::business::load_holidays(args)?);
+            }
+            let weekends = Calendar::default();
+            let options = super::parse_options(args, holidays.as_ref().unwrap_or(&weekends));
+            let found: Vec<(Range<usize>, String)> = time::find_times(&line, &base, options)
+                .into_iter()
+                .filter_map(|(range, result)| {
+                    let date_only = super::is_date_only(&result);
+                    let datetime = choose_datetime(result, prefer)?;
+                    let rendered = match templates.first() {
+                        _ if date_only => s!(datetime.format("%Y-%m-%d")),
+                        Some(template) => template.render(&datetime, target.zone()),
+                        None => target.render(&datetime),
+                    };
+                    Some((range, rendered))
+                })
+                .collect();
+
+            println!("{}", annotate(&line, &found, replace));
+        }
+    }
+
+    Ok(())
+}
+
+fn choose_datetime(result: TimeResult, prefer: Option<DateRule>) -> Option<DateTime<FixedOffset>> {
+    super::choose_one(result, prefer).map(|x| x.datetime)
+}
+
+/// Write each converted time after the text it was read from, or in its
+/// place when `replace` is set. The ranges must be in order and not overlap.
+fn annotate(line: &str, found: &[(Range<usize>, String)], replace: bool) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut last = 0;
+    for (range, rendered) in found {
+        output.push_str(&line[last..range.start]);
+        if replace {
+            output.push_str(rendered);
+        } else {
+            output.push_str(&line[range.clone()]);
+            output.push_str(&format!(" [{}]", rendered));
+        }
+        last = range.end;
+    }
+    output.push_str(&line[last..]);
+    output
+}
+
+#[test]
+fn annotate_marks_each_time() {
+    let line = "at 1543897222 and 2018-12-04 04:20 PST, done";
+    let found = vec![
+        (3..13, s!("2018-12-04T04:20:22+00:00")),
+        (18..38, s!("2018-12-04T12:20:00+00:00")),
+    ];
+
+    assert_eq!(
+        "at 1543897222 [2018-12-04T04:20:22+00:00] and 2018-12-04 04:20 PST [2018-12-04T12:20:00+00:00], done",
+        annotate(line, &found, false)
+    );
+    assert_eq!(
+        "at 2018-12-04T04:20:22+00:00 and 2018-12-04T12:20:00+00:00, done",
+        annotate(line, &found, true)
+    );
+    assert_eq!(line, annotate(line, &[], false));
+}
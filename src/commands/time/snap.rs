@@ -0,0 +1,402 @@
+use clap::ArgMatches;
+
+use chrono::prelude::*;
+use chrono::Duration;
+use chrono_tz::Tz;
+use std::convert::TryFrom;
+use toolkit::time::relative::{self, TimeUnit};
+use toolkit::time::Permutation;
+
+use crate::commands::CliError;
+
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+const NANOS_PER_DAY: i128 = 86_400 * NANOS_PER_SEC;
+
+/// A calendar period an instant can be snapped to the start or end of.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Period {
+    Minute,
+    Hour,
+    Day,
+    /// An ISO week, starting on Monday.
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Period {
+    fn from_name(name: &str) -> Option<Self> {
+        let period = match name {
+            "minute" => Period::Minute,
+            "hour" => Period::Hour,
+            "day" => Period::Day,
+            "week" => Period::Week,
+            "month" => Period::Month,
+            "quarter" => Period::Quarter,
+            "year" => Period::Year,
+            _ => return None,
+        };
+
+        Some(period)
+    }
+
+    /// The wall clock time the period holding `local` starts at.
+    fn start(self, local: NaiveDateTime) -> NaiveDateTime {
+        let date = local.date();
+        match self {
+            Period::Minute => date.and_hms(local.hour(), local.minute(), 0),
+            Period::Hour => date.and_hms(local.hour(), 0, 0),
+            Period::Day => date.and_hms(0, 0, 0),
+            Period::Week => {
+                let days = i64::from(date.weekday().num_days_from_monday());
+                (date - Duration::days(days)).and_hms(0, 0, 0)
+            }
+            Period::Month => NaiveDate::from_ymd(date.year(), date.month(), 1).and_hms(0, 0, 0),
+            Period::Quarter => {
+                NaiveDate::from_ymd(date.year(), date.month0() / 3 * 3 + 1, 1).and_hms(0, 0, 0)
+            }
+            Period::Year => NaiveDate::from_ymd(date.year(), 1, 1).and_hms(0, 0, 0),
+        }
+    }
+
+    /// The wall clock time the period after the one starting at `start`
+    /// starts at.
+    fn next(self, start: NaiveDateTime) -> Option<NaiveDateTime> {
+        let months = match self {
+            Period::Minute => return start.checked_add_signed(Duration::minutes(1)),
+            Period::Hour => return start.checked_add_signed(Duration::hours(1)),
+            Period::Day => return start.checked_add_signed(Duration::days(1)),
+            Period::Week => return start.checked_add_signed(Duration::weeks(1)),
+            Period::Month => 1,
+            Period::Quarter => 3,
+            Period::Year => 12,
+        };
+
+        relative::add_months(start.date(), months).map(|x| x.and_time(start.time()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Nearest,
+    Down,
+    Up,
+}
+
+/// What to snap each time to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Snap {
+    StartOf(Period),
+    /// The last instant of the period, or the start of the next one when
+    /// exclusive.
+    EndOf(Period, bool),
+    Round(Duration, Direction),
+}
+
+pub fn do_time_start_of_command(args: &ArgMatches) -> Result<(), CliError> {
+    do_snap(args, Snap::StartOf(parse_period(args)?))
+}
+
+pub fn do_time_end_of_command(args: &ArgMatches) -> Result<(), CliError> {
+    do_snap(
+        args,
+        Snap::EndOf(parse_period(args)?, args.is_present("exclusive")),
+    )
+}
+
+pub fn do_time_round_command(args: &ArgMatches) -> Result<(), CliError> {
+    let input = args.value_of("INTERVAL").unwrap();
+    let interval = match parse_interval(input) {
+        Some(interval) => interval,
+        None => {
+            error!(
+                "Unable to round to `{}`, use an interval like 5m, 15m, 1h or 1d",
+                input
+            );
+            return Err(CliError::new("Unknown duration", 1));
+        }
+    };
+
+    let direction = if args.is_present("down") {
+        Direction::Down
+    } else if args.is_present("up") {
+        Direction::Up
+    } else {
+        Direction::Nearest
+    };
+
+    do_snap(args, Snap::Round(interval, direction))
+}
+
+fn parse_period(args: &ArgMatches) -> Result<Period, CliError> {
+    let name = args.value_of("UNIT").unwrap();
+    match Period::from_name(name) {
+        Some(period) => Ok(period),
+        None => {
+            error!("Unable to snap to a {}", name);
+            Err(CliError::new("Format error", 2))
+        }
+    }
+}
+
+/// A fixed interval like `15m` or `1h30m`. Weeks and longer aren't always
+/// the same length, so they can't be rounded to.
+fn parse_interval(input: &str) -> Option<Duration> {
+    let mut interval = Duration::zero();
+    for shift in relative::parse_shifts(input)? {
+        let part = match shift.unit {
+            TimeUnit::Nanosecond => Duration::nanoseconds(shift.amount),
+            TimeUnit::Millisecond => Duration::milliseconds(shift.amount),
+            TimeUnit::Second => Duration::seconds(shift.amount),
+            TimeUnit::Minute => Duration::minutes(shift.amount),
+            TimeUnit::Hour => Duration::hours(shift.amount),
+            TimeUnit::Day => Duration::days(shift.amount),
+            _ => return None,
+        };
+        interval = interval.checked_add(&part)?;
+    }
+
+    if interval > Duration::zero() {
+        Some(interval)
+    } else {
+        None
+    }
+}
+
+fn do_snap(args: &ArgMatches, snap: Snap) -> Result<(), CliError> {
+    let base = super::parse_base(args)?;
+    let input: Vec<&str> = args.values_of("INPUT").unwrap().collect();
+    let input = input.join(" ");
+    let holidays = super::business::holidays_for(&input, args)?;
+    let permutations = super::parse_permutations(&input, &base, &holidays, args)?;
+
+    let zone = match args.value_of("in_zone") {
+        Some(zone) => Some(super::parse_zone(zone)?),
+        None => None,
+    };
+
+    let mut results = Vec::new();
+    for permutation in permutations {
+        let datetime = permutation.datetime;
+        match snap_time(datetime, zone.or(permutation.zone), snap) {
+            Some(snapped) => results.push(Permutation {
+                datetime: snapped,
+                ..permutation
+            }),
+            None => {
+                error!("Snapping {} is out of range", datetime);
+                return Err(CliError::new("Out of range", 1));
+            }
+        }
+    }
+
+    super::render_results(&results, args)
+}
+
+/// Snap on the wall clock of `zone`. Without one, a time on the local clock
+/// follows the local zone, and any other stays in the offset it was written in.
+fn snap_time(
+    datetime: DateTime<FixedOffset>,
+    zone: Option<Tz>,
+    snap: Snap,
+) -> Option<DateTime<FixedOffset>> {
+    match zone {
+        Some(zone) => snap_in(&datetime.with_timezone(&zone), snap)
+            .map(|x| x.with_timezone(&x.offset().fix())),
+        None if super::is_local(&datetime) => snap_in(&datetime.with_timezone(&Local), snap)
+            .map(|x| x.with_timezone(&x.offset().fix())),
+        None => snap_in(&datetime, snap),
+    }
+}
+
+fn snap_in<Z: TimeZone>(datetime: &DateTime<Z>, snap: Snap) -> Option<DateTime<Z>> {
+    match snap {
+        Snap::StartOf(period) => boundary(datetime, period.start(datetime.naive_local()), period),
+        Snap::EndOf(period, exclusive) => {
+            let start = boundary(datetime, period.start(datetime.naive_local()), period)?;
+            let next = boundary(&start, period.next(start.naive_local())?, period)?;
+            if exclusive {
+                Some(next)
+            } else {
+                relative::add_elapsed(&next, Duration::nanoseconds(-1))
+            }
+        }
+        Snap::Round(interval, direction) => round(datetime, interval, direction),
+    }
+}
+
+/// Map a wall clock boundary near `datetime` back to an instant. Minutes and
+/// hours stay in `datetime`'s offset, so the repeated hour when the clocks go
+/// back keeps its own start; days and longer are read in the zone.
+fn boundary<Z: TimeZone>(
+    datetime: &DateTime<Z>,
+    local: NaiveDateTime,
+    period: Period,
+) -> Option<DateTime<Z>> {
+    match period {
+        Period::Minute | Period::Hour => in_offset(datetime, &local),
+        _ => relative::from_local(&datetime.timezone(), &local),
+    }
+}
+
+fn in_offset<Z: TimeZone>(datetime: &DateTime<Z>, local: &NaiveDateTime) -> Option<DateTime<Z>> {
+    datetime
+        .offset()
+        .fix()
+        .from_local_datetime(local)
+        .single()
+        .map(|x| x.with_timezone(&datetime.timezone()))
+}
+
+/// Round to a multiple of `interval` on the wall clock, so hours line up with
+/// the zone's hours even when its offset isn't whole hours. Halfway rounds up.
+fn round<Z: TimeZone>(
+    datetime: &DateTime<Z>,
+    interval: Duration,
+    direction: Direction,
+) -> Option<DateTime<Z>> {
+    let step = i128::from(interval.num_nanoseconds()?);
+    let local = datetime.naive_local();
+    let nanos =
+        i128::from(local.timestamp()) * NANOS_PER_SEC + i128::from(local.timestamp_subsec_nanos());
+
+    let remainder = nanos.rem_euclid(step);
+    let down = nanos - remainder;
+    let rounded = match direction {
+        Direction::Down => down,
+        Direction::Up if remainder == 0 => down,
+        Direction::Nearest if remainder * 2 < step => down,
+        Direction::Up | Direction::Nearest => down + step,
+    };
+
+    let seconds = i64::try_from(rounded.div_euclid(NANOS_PER_SEC)).ok()?;
+    let rounded =
+        NaiveDateTime::from_timestamp_opt(seconds, rounded.rem_euclid(NANOS_PER_SEC) as u32)?;
+
+    if step % NANOS_PER_DAY == 0 {
+        relative::from_local(&datetime.timezone(), &rounded)
+    } else {
+        in_offset(datetime, &rounded)
+    }
+}
+
+#[cfg(test)]
+fn snap_at(input: &str, zone: chrono_tz::Tz, snap: Snap) -> String {
+    let datetime = DateTime::parse_from_rfc3339(input).unwrap();
+    snap_in(&datetime.with_timezone(&zone), snap)
+        .unwrap()
+        .to_rfc3339()
+}
+
+#[test]
+fn snap_to_calendar_periods() {
+    let at = "2018-11-15T10:07:30-05:00";
+    let start = |period| snap_at(at, Tz::America__New_York, Snap::StartOf(period));
+    assert_eq!("2018-11-15T10:07:00-05:00", start(Period::Minute));
+    assert_eq!("2018-11-15T10:00:00-05:00", start(Period::Hour));
+    assert_eq!("2018-11-15T00:00:00-05:00", start(Period::Day));
+    assert_eq!("2018-11-12T00:00:00-05:00", start(Period::Week));
+    assert_eq!("2018-11-01T00:00:00-04:00", start(Period::Month));
+    assert_eq!("2018-10-01T00:00:00-04:00", start(Period::Quarter));
+    assert_eq!("2018-01-01T00:00:00-05:00", start(Period::Year));
+
+    let end =
+        |period, exclusive| snap_at(at, Tz::America__New_York, Snap::EndOf(period, exclusive));
+    assert_eq!(
+        "2018-11-15T23:59:59.999999999-05:00",
+        end(Period::Day, false)
+    );
+    assert_eq!("2018-11-16T00:00:00-05:00", end(Period::Day, true));
+    assert_eq!(
+        "2018-11-18T23:59:59.999999999-05:00",
+        end(Period::Week, false)
+    );
+    assert_eq!("2019-01-01T00:00:00-05:00", end(Period::Quarter, true));
+
+    // The day the clocks went back was 25 hours long, and the repeated hour
+    // starts in its own offset.
+    let fall_back = "2018-11-04T06:30:00+00:00";
+    assert_eq!(
+        "2018-11-04T01:00:00-05:00",
+        snap_at(
+            fall_back,
+            Tz::America__New_York,
+            Snap::StartOf(Period::Hour)
+        )
+    );
+    assert_eq!(
+        "2018-11-04T00:00:00-04:00",
+        snap_at(fall_back, Tz::America__New_York, Snap::StartOf(Period::Day))
+    );
+    assert_eq!(
+        "2018-11-05T00:00:00-05:00",
+        snap_at(
+            fall_back,
+            Tz::America__New_York,
+            Snap::EndOf(Period::Day, true)
+        )
+    );
+}
+
+#[test]
+fn snap_keeps_the_offset_it_was_given() {
+    let datetime = DateTime::parse_from_rfc3339("2018-12-04T04:20:22+05:30").unwrap();
+
+    assert_eq!(
+        Some(s!("2018-12-04T00:00:00+05:30")),
+        snap_time(datetime, None, Snap::StartOf(Period::Day)).map(|x| x.to_rfc3339())
+    );
+    assert_eq!(
+        Some(s!("2018-12-03T00:00:00+00:00")),
+        snap_time(datetime, Some(chrono_tz::UTC), Snap::StartOf(Period::Day))
+            .map(|x| x.to_rfc3339())
+    );
+}
+
+#[test]
+fn round_to_intervals() {
+    let round = |input, interval: &str, direction| {
+        let interval = parse_interval(interval).unwrap();
+        snap_at(input, Tz::UTC, Snap::Round(interval, direction))
+    };
+    assert_eq!(
+        "2018-12-04T10:05:00+00:00",
+        round("2018-12-04T10:07:29Z", "5m", Direction::Nearest)
+    );
+    assert_eq!(
+        "2018-12-04T10:15:00+00:00",
+        round("2018-12-04T10:07:30Z", "15m", Direction::Nearest)
+    );
+    assert_eq!(
+        "2018-12-04T10:00:00+00:00",
+        round("2018-12-04T10:14:59Z", "15m", Direction::Down)
+    );
+    assert_eq!(
+        "2018-12-04T10:15:00+00:00",
+        round("2018-12-04T10:00:01Z", "15m", Direction::Up)
+    );
+    assert_eq!(
+        "2018-12-04T10:00:00+00:00",
+        round("2018-12-04T10:00:00Z", "15m", Direction::Up)
+    );
+    assert_eq!(
+        "2018-12-05T00:00:00+00:00",
+        round("2018-12-04T12:00:00Z", "1d", Direction::Nearest)
+    );
+
+    // Hours line up with the zone's own hours.
+    let interval = parse_interval("1h").unwrap();
+    assert_eq!(
+        "2018-12-04T11:00:00+05:30",
+        snap_at(
+            "2018-12-04T10:40:00+05:30",
+            Tz::Asia__Kolkata,
+            Snap::Round(interval, Direction::Nearest)
+        )
+    );
+
+    assert_eq!(None, parse_interval("1 month"));
+    assert_eq!(None, parse_interval("0m"));
+    assert_eq!(Some(Duration::minutes(90)), parse_interval("1h30m"));
+}
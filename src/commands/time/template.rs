@@ -0,0 +1,94 @@
+use std::str::FromStr;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::prelude::*;
+use chrono_tz::Tz;
+
+/// A `--format` value: a strftime pattern or a named preset, optionally
+/// prefixed with the zone to render in, like `Europe/Berlin=%H:%M %Z`.
+#[derive(Debug, PartialEq)]
+pub struct OutputTemplate {
+    zone: Option<Tz>,
+    pattern: String,
+}
+
+/// Named patterns, and whether they're always written in UTC.
+fn preset(name: &str) -> Option<(&'static str, bool)> {
+    let preset = match name.trim().to_lowercase().as_str() {
+        "rfc3339" | "iso" => ("%Y-%m-%dT%H:%M:%S%.f%:z", false),
+        "rfc2822" => ("%a, %d %b %Y %H:%M:%S %z", false),
+        "http-date" => ("%a, %d %b %Y %H:%M:%S GMT", true),
+        "syslog" => ("%b %e %H:%M:%S", false),
+        "java-default" => ("%a %b %d %H:%M:%S %Z %Y", false),
+        _ => return None,
+    };
+
+    Some(preset)
+}
+
+impl OutputTemplate {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let (zone, pattern) = match input.find('=') {
+            Some(index) => match Tz::from_str(&input[..index]) {
+                Ok(zone) => (Some(zone), &input[index + 1..]),
+                Err(_) => (None, input),
+            },
+            None => (None, input),
+        };
+
+        let (zone, pattern) = match preset(pattern) {
+            Some((pattern, true)) => (Some(chrono_tz::UTC), s!(pattern)),
+            Some((pattern, false)) => (zone, s!(pattern)),
+            None => (zone, s!(pattern)),
+        };
+
+        if StrftimeItems::new(&pattern).any(|x| x == Item::Error) {
+            return Err(format!("Invalid format `{}`", input));
+        }
+
+        Ok(OutputTemplate { zone, pattern })
+    }
+
+    /// Render `datetime` in the template's zone, or else in `zone`, the zone
+    /// the input was written in.
+    pub fn render(&self, datetime: &DateTime<FixedOffset>, zone: Option<Tz>) -> String {
+        match self.zone.or(zone) {
+            Some(zone) => format!("{}", datetime.with_timezone(&zone).format(&self.pattern)),
+            None => format!("{}", datetime.format(&self.pattern)),
+        }
+    }
+}
+
+#[test]
+fn templates_and_presets() {
+    let datetime = FixedOffset::west(8 * 3600)
+        .ymd(2018, 12, 3)
+        .and_hms_milli(20, 20, 22, 205);
+    let render = |input: &str| {
+        OutputTemplate::parse(input)
+            .unwrap()
+            .render(&datetime, None)
+    };
+
+    assert_eq!(
+        "2018-12-03T20:20:22.205-08:00",
+        render("%Y-%m-%dT%H:%M:%S%.3f%:z")
+    );
+    assert_eq!("2018-12-03T20:20:22.205-08:00", render("rfc3339"));
+    assert_eq!("Mon, 03 Dec 2018 20:20:22 -0800", render("rfc2822"));
+    assert_eq!("Tue, 04 Dec 2018 04:20:22 GMT", render("http-date"));
+    assert_eq!("Dec  3 20:20:22", render("syslog"));
+    assert_eq!(
+        "Tue Dec 04 05:20:22 CET 2018",
+        render("Europe/Berlin=java-default")
+    );
+    assert_eq!("05:20 CET", render("Europe/Berlin=%H:%M %Z"));
+    assert_eq!("a=b", render("a=b"));
+    assert!(OutputTemplate::parse("%Y-%").is_err());
+
+    let template = OutputTemplate::parse("%H:%M %Z").unwrap();
+    assert_eq!(
+        "20:20 PST",
+        template.render(&datetime, Some(chrono_tz::America::Los_Angeles))
+    );
+}
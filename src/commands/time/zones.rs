@@ -0,0 +1,103 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use chrono_tz::Tz;
+use clap::ArgMatches;
+
+use crate::commands::CliError;
+
+pub const ZONES_ENV: &str = "TOOLKIT_TIME_ZONES";
+
+const DEFAULT_ZONES: [Tz; 5] = [
+    chrono_tz::US::Eastern,
+    chrono_tz::US::Central,
+    chrono_tz::US::Mountain,
+    chrono_tz::US::Arizona,
+    chrono_tz::US::Pacific,
+];
+
+/// The zones every instant is also rendered in. Taken from `--zone`, then the
+/// `TOOLKIT_TIME_ZONES` environment variable (comma separated), then the
+/// `toolkit/zones` file in the user's config directory (one zone per line),
+/// and finally the US zones.
+pub fn display_zones(args: &ArgMatches) -> Result<Vec<Tz>, CliError> {
+    if let Some(zones) = args.values_of("zone") {
+        return zones.map(|x| super::parse_zone(x)).collect();
+    }
+
+    if let Ok(zones) = env::var(ZONES_ENV) {
+        return parse_zone_list(&zones.replace(",", "\n")).map_err(|zone| {
+            error!("Unknown time zone `{}` in {}", zone, ZONES_ENV);
+            CliError::new("Unknown time zone", 1)
+        });
+    }
+
+    if let Some(path) = config_path("zones") {
+        if let Ok(contents) = fs::read_to_string(&path) {
+            return parse_zone_list(&contents).map_err(|zone| {
+                error!("Unknown time zone `{}` in {}", zone, path.display());
+                CliError::new("Unknown time zone", 1)
+            });
+        }
+    }
+
+    Ok(DEFAULT_ZONES.to_vec())
+}
+
+/// A file in the user's `toolkit` config directory.
+pub fn config_path(name: &str) -> Option<PathBuf> {
+    let config_dir = env::var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| env::var("HOME").map(|x| PathBuf::from(x).join(".config")))
+        .or_else(|_| env::var("APPDATA").map(PathBuf::from))
+        .ok()?;
+
+    Some(config_dir.join("toolkit").join(name))
+}
+
+/// Parse one zone per line, ignoring blank lines and `#` comments. Returns the
+/// first zone that isn't understood as the error.
+fn parse_zone_list(contents: &str) -> Result<Vec<Tz>, String> {
+    let mut zones = Vec::new();
+
+    for line in contents.lines() {
+        let line = match line.find('#') {
+            Some(index) => &line[..index],
+            None => line,
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match Tz::from_str(line) {
+            Ok(zone) => zones.push(zone),
+            Err(_) => return Err(s!(line)),
+        }
+    }
+
+    if zones.is_empty() {
+        Ok(DEFAULT_ZONES.to_vec())
+    } else {
+        Ok(zones)
+    }
+}
+
+#[test]
+fn zone_list_from_config() {
+    assert_eq!(
+        Ok(vec![
+            chrono_tz::Europe::Berlin,
+            chrono_tz::Asia::Kolkata,
+            chrono_tz::UTC
+        ]),
+        parse_zone_list("# team\nEurope/Berlin\n\n  Asia/Kolkata # Pune\nUTC\n")
+    );
+    assert_eq!(Ok(DEFAULT_ZONES.to_vec()), parse_zone_list("# nothing\n"));
+    assert_eq!(
+        Err(s!("Mars/Olympus_Mons")),
+        parse_zone_list("Europe/Berlin\nMars/Olympus_Mons")
+    );
+}
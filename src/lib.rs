@@ -0,0 +1,14 @@
+//! The parts of toolkit that are useful outside of the command line, so other
+//! programs can read times the same forgiving way `toolkit time` does.
+
+extern crate chrono;
+extern crate chrono_tz;
+#[macro_use]
+extern crate kopy_common_lib;
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate log;
+extern crate regex;
+
+pub mod time;
@@ -40,7 +40,12 @@ use commands::har::exec::do_har_command;
 use commands::json::*;
 use commands::nsq::post::do_send_command;
 use commands::nsq::stats::do_stats_command;
-use commands::time::do_time_command;
+use commands::time::{
+    do_time_add_command, do_time_command, do_time_cron_command, do_time_diff_command,
+    do_time_end_of_command, do_time_is_business_day_command, do_time_overlap_command,
+    do_time_pattern_command, do_time_range_command, do_time_round_command, do_time_scan_command,
+    do_time_start_of_command, do_time_sub_command, do_time_zones_command,
+};
 use kopy_common_lib::configure_logging;
 
 lazy_static! {
@@ -65,10 +70,29 @@ fn main() {
     );
 
     let result = match matches.subcommand() {
-        ("time", Some(time_matches)) => do_time_command(time_matches),
+        ("time", Some(time_matches)) => match time_matches.subcommand() {
+            ("parse", Some(parse_matches)) => do_time_command(parse_matches),
+            ("diff", Some(diff_matches)) => do_time_diff_command(diff_matches),
+            ("add", Some(add_matches)) => do_time_add_command(add_matches),
+            ("sub", Some(sub_matches)) => do_time_sub_command(sub_matches),
+            ("start-of", Some(start_matches)) => do_time_start_of_command(start_matches),
+            ("end-of", Some(end_matches)) => do_time_end_of_command(end_matches),
+            ("round", Some(round_matches)) => do_time_round_command(round_matches),
+            ("range", Some(range_matches)) => do_time_range_command(range_matches),
+            ("zones", Some(zones_matches)) => do_time_zones_command(zones_matches),
+            ("overlap", Some(overlap_matches)) => do_time_overlap_command(overlap_matches),
+            ("cron", Some(cron_matches)) => do_time_cron_command(cron_matches),
+            ("is-business-day", Some(business_matches)) => {
+                do_time_is_business_day_command(business_matches)
+            }
+            ("scan", Some(scan_matches)) => do_time_scan_command(scan_matches),
+            ("pattern", Some(pattern_matches)) => do_time_pattern_command(pattern_matches),
+            _ => do_time_command(time_matches),
+        },
         ("har", Some(har_matches)) => do_har_command(har_matches),
         ("json", Some(json_matches)) => match json_matches.subcommand() {
             ("latest", Some(filter_matches)) => do_json_latest_command(filter_matches),
+            ("retime", Some(retime_matches)) => do_json_retime_command(retime_matches),
             ("sql", Some(filter_matches)) => do_json_sql_command(filter_matches),
             _ => unreachable!(),
         },
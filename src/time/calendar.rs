@@ -0,0 +1,444 @@
+use std::collections::HashSet;
+
+use chrono::prelude::*;
+use chrono::Duration;
+
+/// The longest run of days without a business day before giving up, so a
+/// calendar that's all holidays doesn't loop forever.
+const MAX_DAYS_OFF: u32 = 366;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum HolidayDate {
+    Once(NaiveDate),
+    /// The same month and day every year.
+    Yearly(u32, u32),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct Holiday {
+    name: String,
+    date: HolidayDate,
+}
+
+impl Holiday {
+    fn matches(&self, date: NaiveDate) -> bool {
+        match self.date {
+            HolidayDate::Once(day) => day == date,
+            HolidayDate::Yearly(month, day) => date.month() == month && date.day() == day,
+        }
+    }
+}
+
+/// The days business days skip: weekends, and any holidays that were loaded.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Calendar {
+    holidays: Vec<Holiday>,
+}
+
+impl Calendar {
+    /// Parse an ICS calendar, or a text file with a date and a name on each
+    /// line. `2018-12-25 Christmas Day` is one day, and `12-25 Christmas Day`
+    /// is every year.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let holidays = if contents.trim_start().starts_with("BEGIN:VCALENDAR") {
+            parse_ics(contents)?
+        } else {
+            parse_text(contents)?
+        };
+
+        Ok(Calendar { holidays })
+    }
+
+    /// Add the holidays in `other` to this calendar.
+    pub fn extend(&mut self, other: Calendar) {
+        self.holidays.extend(other.holidays);
+    }
+
+    /// The name of the holiday on `date`, if it is one.
+    pub fn holiday(&self, date: NaiveDate) -> Option<&str> {
+        self.holidays
+            .iter()
+            .find(|x| x.matches(date))
+            .map(|x| x.name.as_str())
+    }
+
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        is_weekday(date) && self.holiday(date).is_none()
+    }
+
+    /// Move `amount` business days from `date`. Starting on a weekend or
+    /// holiday, the first business day counts as one.
+    pub fn add_business_days(&self, date: NaiveDate, amount: i64) -> Option<NaiveDate> {
+        let mut current = date;
+        let mut remaining = amount.abs();
+        let mut days_off = 0;
+
+        while remaining > 0 {
+            current = if amount > 0 {
+                current.succ_opt()?
+            } else {
+                current.pred_opt()?
+            };
+
+            if self.is_business_day(current) {
+                remaining -= 1;
+                days_off = 0;
+            } else {
+                days_off += 1;
+                if days_off > MAX_DAYS_OFF {
+                    return None;
+                }
+            }
+        }
+
+        Some(current)
+    }
+
+    /// How many business days after `start` up to and including `end`, which
+    /// is how many `add_business_days` would move. Negative when `end` comes
+    /// first.
+    pub fn business_days_between(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+        if end < start {
+            return -self.business_days_between(end, start);
+        }
+
+        // Every whole week has five weekdays, so only the days left over need
+        // looking at one by one.
+        let days = end.signed_duration_since(start).num_days();
+        let mut count = days / 7 * 5;
+        let mut day = start + Duration::days(days / 7 * 7);
+        while day < end {
+            day = day.succ();
+            if is_weekday(day) {
+                count += 1;
+            }
+        }
+
+        count - self.weekday_holidays(start, end).len() as i64
+    }
+
+    /// Every weekday after `start` up to and including `end` that's a holiday.
+    fn weekday_holidays(&self, start: NaiveDate, end: NaiveDate) -> HashSet<NaiveDate> {
+        let mut days = HashSet::new();
+        for holiday in &self.holidays {
+            match holiday.date {
+                HolidayDate::Once(date) => {
+                    days.insert(date);
+                }
+                HolidayDate::Yearly(month, day) => days.extend(
+                    (start.year()..=end.year())
+                        .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day)),
+                ),
+            }
+        }
+
+        days.retain(|x| *x > start && *x <= end && is_weekday(*x));
+        days
+    }
+}
+
+fn is_weekday(date: NaiveDate) -> bool {
+    date.weekday() != Weekday::Sat && date.weekday() != Weekday::Sun
+}
+
+fn parse_text(contents: &str) -> Result<Vec<Holiday>, String> {
+    let mut holidays = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = match line.find('#') {
+            Some(index) => &line[..index],
+            None => line,
+        }
+        .trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let (date, name) = match line.find(char::is_whitespace) {
+            Some(split) => (&line[..split], line[split..].trim()),
+            None => (line, "Holiday"),
+        };
+
+        match parse_holiday_date(date) {
+            Some(date) => holidays.push(Holiday {
+                name: s!(name),
+                date,
+            }),
+            None => {
+                return Err(format!(
+                    "line {}: `{}` isn't a date like 2018-12-25 or 12-25",
+                    index + 1,
+                    date
+                ))
+            }
+        }
+    }
+
+    Ok(holidays)
+}
+
+fn parse_holiday_date(input: &str) -> Option<HolidayDate> {
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Some(HolidayDate::Once(date));
+    }
+
+    let mut parts = input.splitn(2, '-');
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+
+    // 2000 was a leap year, so Feb 29 is allowed.
+    NaiveDate::from_ymd_opt(2000, month, day)?;
+    Some(HolidayDate::Yearly(month, day))
+}
+
+/// The parts of a VEVENT we use. Dates are the date, and whether it had a
+/// time after midnight.
+#[derive(Debug, Default)]
+struct IcsEvent {
+    start: Option<(NaiveDate, bool)>,
+    end: Option<(NaiveDate, bool)>,
+    summary: String,
+    rule: Option<String>,
+}
+
+impl IcsEvent {
+    /// Every day the event covers. An all day event's end date is the day
+    /// after it finishes, an event with times ends on its end date.
+    fn into_holidays(self) -> Result<Vec<Holiday>, String> {
+        let start = match self.start {
+            Some((start, _)) => start,
+            None => return Err(format!("event `{}` has no DTSTART", self.summary)),
+        };
+
+        let last = match self.end {
+            Some((end, true)) => end,
+            Some((end, false)) if end > start => end.pred(),
+            _ => start,
+        };
+
+        let yearly = match &self.rule {
+            Some(rule) => {
+                let rule = rule.to_uppercase();
+                let yearly = rule.contains("FREQ=YEARLY") && !rule.contains("BYDAY");
+                if !yearly {
+                    warn!(
+                        "Only repeating on the same day every year is understood, `{}` is only counted on {}",
+                        self.summary, start
+                    );
+                }
+                yearly
+            }
+            None => false,
+        };
+
+        let name = if self.summary.is_empty() {
+            s!("Holiday")
+        } else {
+            self.summary
+        };
+
+        let mut holidays = Vec::new();
+        let mut day = start;
+        while day <= last && holidays.len() < MAX_DAYS_OFF as usize {
+            holidays.push(Holiday {
+                name: name.clone(),
+                date: if yearly {
+                    HolidayDate::Yearly(day.month(), day.day())
+                } else {
+                    HolidayDate::Once(day)
+                },
+            });
+            day = day.succ();
+        }
+
+        Ok(holidays)
+    }
+}
+
+fn parse_ics(contents: &str) -> Result<Vec<Holiday>, String> {
+    // Long lines are folded onto lines starting with whitespace.
+    let mut lines: Vec<String> = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim_end_matches('\r');
+        match lines.last_mut() {
+            Some(last) if line.starts_with(' ') || line.starts_with('\t') => {
+                last.push_str(&line[1..])
+            }
+            _ => lines.push(s!(line)),
+        }
+    }
+
+    let mut holidays = Vec::new();
+    let mut event: Option<IcsEvent> = None;
+    for line in &lines {
+        let (name, value) = match line.find(':') {
+            Some(index) => (&line[..index], &line[index + 1..]),
+            None => continue,
+        };
+        let property = name.split(';').next().unwrap_or_default().to_uppercase();
+
+        match (property.as_str(), event.as_mut()) {
+            ("BEGIN", _) if value == "VEVENT" => event = Some(IcsEvent::default()),
+            ("END", Some(_)) if value == "VEVENT" => {
+                holidays.append(&mut event.take().unwrap().into_holidays()?)
+            }
+            ("DTSTART", Some(event)) => event.start = Some(parse_ics_date(value)?),
+            ("DTEND", Some(event)) => event.end = Some(parse_ics_date(value)?),
+            ("SUMMARY", Some(event)) => event.summary = unescape_ics(value),
+            ("RRULE", Some(event)) => event.rule = Some(s!(value)),
+            _ => {}
+        }
+    }
+
+    Ok(holidays)
+}
+
+/// `20181225`, or `20181225T090000Z` with a time.
+fn parse_ics_date(value: &str) -> Result<(NaiveDate, bool), String> {
+    let date = value
+        .get(..8)
+        .and_then(|x| NaiveDate::parse_from_str(x, "%Y%m%d").ok())
+        .ok_or_else(|| format!("`{}` isn't an ICS date", value))?;
+    let time = value[8..].trim_end_matches('Z');
+
+    Ok((date, !time.is_empty() && time != "T000000"))
+}
+
+fn unescape_ics(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+#[test]
+fn business_days_skip_weekends_and_holidays() {
+    let date = |y, m, d| NaiveDate::from_ymd(y, m, d);
+    let calendar = Calendar::parse(
+        "# Office holidays\n\
+         2018-12-24 Christmas Eve\n\
+         12-25 Christmas Day\n\
+         \n\
+         2019-01-01\n",
+    )
+    .unwrap();
+
+    assert_eq!(Some("Christmas Day"), calendar.holiday(date(2019, 12, 25)));
+    assert_eq!(Some("Holiday"), calendar.holiday(date(2019, 1, 1)));
+    assert_eq!(None, calendar.holiday(date(2019, 12, 24)));
+    assert!(!calendar.is_business_day(date(2018, 12, 22)));
+    assert!(calendar.is_business_day(date(2018, 12, 21)));
+
+    // Friday + 1 is Monday, but here Monday and Tuesday are holidays.
+    let weekends = Calendar::default();
+    assert_eq!(
+        Some(date(2018, 12, 24)),
+        weekends.add_business_days(date(2018, 12, 21), 1)
+    );
+    assert_eq!(
+        Some(date(2018, 12, 26)),
+        calendar.add_business_days(date(2018, 12, 21), 1)
+    );
+    assert_eq!(
+        Some(date(2018, 12, 21)),
+        calendar.add_business_days(date(2018, 12, 26), -1)
+    );
+    // Starting on a Saturday, Monday is the first business day.
+    assert_eq!(
+        Some(date(2018, 12, 10)),
+        weekends.add_business_days(date(2018, 12, 8), 1)
+    );
+    assert_eq!(
+        Some(date(2018, 12, 8)),
+        weekends.add_business_days(date(2018, 12, 8), 0)
+    );
+
+    assert_eq!(
+        5,
+        weekends.business_days_between(date(2018, 12, 21), date(2018, 12, 28))
+    );
+    assert_eq!(
+        3,
+        calendar.business_days_between(date(2018, 12, 21), date(2018, 12, 28))
+    );
+    assert_eq!(
+        -3,
+        calendar.business_days_between(date(2018, 12, 28), date(2018, 12, 21))
+    );
+    assert_eq!(
+        0,
+        calendar.business_days_between(date(2018, 12, 21), date(2018, 12, 21))
+    );
+
+    // Counting whole weeks agrees with counting every day.
+    for (start, end) in &[
+        (date(2016, 2, 27), date(2024, 3, 1)),
+        (date(2018, 12, 23), date(2019, 1, 5)),
+        (date(1999, 12, 31), date(2000, 1, 1)),
+    ] {
+        let mut expected = 0;
+        let mut day = *start;
+        while day < *end {
+            day = day.succ();
+            if calendar.is_business_day(day) {
+                expected += 1;
+            }
+        }
+        assert_eq!(expected, calendar.business_days_between(*start, *end));
+    }
+
+    assert!(Calendar::parse("2018-13-01 Nope").is_err());
+    assert!(Calendar::parse("02-30 Nope").is_err());
+
+    // A calendar without any business days gives up instead of looping.
+    let every_day = Calendar {
+        holidays: (0..400)
+            .map(|x| Holiday {
+                name: s!("Closed"),
+                date: HolidayDate::Once(date(2018, 12, 22) + chrono::Duration::days(x)),
+            })
+            .collect(),
+    };
+    assert_eq!(None, every_day.add_business_days(date(2018, 12, 21), 1));
+}
+
+#[test]
+fn holidays_from_ics() {
+    let date = |y, m, d| NaiveDate::from_ymd(y, m, d);
+    let calendar = Calendar::parse(
+        "BEGIN:VCALENDAR\r\n\
+         VERSION:2.0\r\n\
+         BEGIN:VEVENT\r\n\
+         DTSTART;VALUE=DATE:20181225\r\n\
+         DTEND;VALUE=DATE:20181227\r\n\
+         SUMMARY:Christmas Day\\, and Boxing\r\n  Day\r\n\
+         END:VEVENT\r\n\
+         BEGIN:VEVENT\r\n\
+         DTSTART;VALUE=DATE:20190101\r\n\
+         RRULE:FREQ=YEARLY\r\n\
+         SUMMARY:New Year's Day\r\n\
+         END:VEVENT\r\n\
+         BEGIN:VEVENT\r\n\
+         DTSTART:20181122T000000Z\r\n\
+         RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=4TH\r\n\
+         SUMMARY:Thanksgiving\r\n\
+         END:VEVENT\r\n\
+         END:VCALENDAR\r\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        Some("Christmas Day, and Boxing Day"),
+        calendar.holiday(date(2018, 12, 26))
+    );
+    assert_eq!(None, calendar.holiday(date(2018, 12, 27)));
+    assert_eq!(Some("New Year's Day"), calendar.holiday(date(2021, 1, 1)));
+    // Only the fixed date of a rule we don't understand counts.
+    assert_eq!(Some("Thanksgiving"), calendar.holiday(date(2018, 11, 22)));
+    assert_eq!(None, calendar.holiday(date(2019, 11, 22)));
+
+    assert!(Calendar::parse("BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:x\nEND:VEVENT\n").is_err());
+}
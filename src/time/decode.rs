@@ -0,0 +1,309 @@
+use regex::Regex;
+
+lazy_static! {
+    static ref UUID: Regex = Regex::new(
+        r"(?i)^\{?(?:urn:uuid:)?(?P<time_low>[0-9a-f]{8})-(?P<time_mid>[0-9a-f]{4})-(?P<version>[0-9a-f])(?P<time_high>[0-9a-f]{3})-[0-9a-f]{4}-[0-9a-f]{12}\}?$"
+    )
+    .unwrap();
+    static ref ULID: Regex = Regex::new(r"(?i)^[0-7][0-9A-HJKMNP-TV-Z]{25}$").unwrap();
+    static ref DECIMAL: Regex =
+        Regex::new(r"^(?P<sign>-)?(?P<whole>\d+)(\.(?P<fraction>\d+))?$").unwrap();
+}
+
+const NANO_PER_SEC: i128 = 1_000_000_000;
+const NANO_PER_MILLI: i128 = 1_000_000;
+const NANO_PER_DAY: i128 = 86_400 * NANO_PER_SEC;
+
+/// How far each system's zero is from the Unix epoch.
+const EXCEL_DAYS_TO_UNIX: i128 = 25_569;
+const FILETIME_SECONDS_TO_UNIX: i128 = 11_644_473_600;
+const NTP_SECONDS_TO_UNIX: i128 = 2_208_988_800;
+const COCOA_SECONDS_FROM_UNIX: i128 = 978_307_200;
+const GPS_SECONDS_FROM_UNIX: i128 = 315_964_800;
+const TICKS_SECONDS_TO_UNIX: i128 = 62_135_596_800;
+const GREGORIAN_SECONDS_TO_UNIX: i128 = 12_219_292_800;
+const TWITTER_EPOCH_MILLIS: i128 = 1_288_834_974_657;
+const DISCORD_EPOCH_MILLIS: i128 = 1_420_070_400_000;
+
+/// The years a ULID found without `--as ulid` has to fall in (2000 to 2200),
+/// so any other 26 character word isn't taken for one.
+const ULID_DETECT_SECONDS: std::ops::Range<i128> = 946_684_800..7_258_118_400;
+
+/// The Unix times where a leap second was added since GPS time started. GPS
+/// time doesn't have them, so it runs ahead of UTC by one for each.
+const LEAP_SECONDS: [i128; 18] = [
+    362_793_600,
+    394_329_600,
+    425_865_600,
+    489_024_000,
+    567_993_600,
+    631_152_000,
+    662_688_000,
+    709_948_800,
+    741_484_800,
+    773_020_800,
+    820_454_400,
+    867_715_200,
+    915_148_800,
+    1_136_073_600,
+    1_230_768_000,
+    1_341_100_800,
+    1_435_708_800,
+    1_483_228_800,
+];
+
+/// A timestamp format other than a Unix epoch, or an ID with a timestamp
+/// inside it.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Encoding {
+    Excel,
+    FileTime,
+    Ntp,
+    Cocoa,
+    Gps,
+    Ticks,
+    Twitter,
+    Discord,
+    Uuid,
+    Ulid,
+}
+
+impl Encoding {
+    /// The encoding named by `--as`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let encoding = match name.trim().to_lowercase().as_str() {
+            "excel" => Encoding::Excel,
+            "filetime" => Encoding::FileTime,
+            "ntp" => Encoding::Ntp,
+            "cocoa" => Encoding::Cocoa,
+            "gps" => Encoding::Gps,
+            "ticks" => Encoding::Ticks,
+            "twitter" => Encoding::Twitter,
+            "discord" => Encoding::Discord,
+            "uuid" => Encoding::Uuid,
+            "ulid" => Encoding::Ulid,
+            _ => return None,
+        };
+
+        Some(encoding)
+    }
+
+    /// The encoding `input` is unmistakably in, without being asked for.
+    pub fn detect(input: &str) -> Option<Self> {
+        if UUID.is_match(input) {
+            Some(Encoding::Uuid)
+        } else {
+            match decode_ulid(input) {
+                Some(nanos) if ULID_DETECT_SECONDS.contains(&(nanos / NANO_PER_SEC)) => {
+                    Some(Encoding::Ulid)
+                }
+                _ => None,
+            }
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Encoding::Excel => "Excel serial date",
+            Encoding::FileTime => "Windows FILETIME",
+            Encoding::Ntp => "NTP timestamp",
+            Encoding::Cocoa => "Apple Cocoa reference date",
+            Encoding::Gps => "GPS time",
+            Encoding::Ticks => ".NET ticks",
+            Encoding::Twitter => "Twitter snowflake ID",
+            Encoding::Discord => "Discord snowflake ID",
+            Encoding::Uuid => "UUID timestamp",
+            Encoding::Ulid => "ULID timestamp",
+        }
+    }
+
+    /// Nanoseconds since the Unix epoch that `input` encodes.
+    pub fn decode(self, input: &str) -> Option<i128> {
+        let input = input.trim();
+        match self {
+            Encoding::Excel => {
+                let days = scaled(input, NANO_PER_DAY)?;
+                if days < 0 {
+                    return None;
+                }
+                Some(days - EXCEL_DAYS_TO_UNIX * NANO_PER_DAY)
+            }
+            Encoding::FileTime => {
+                Some(integer(input)? * 100 - FILETIME_SECONDS_TO_UNIX * NANO_PER_SEC)
+            }
+            Encoding::Ntp => decode_ntp(input),
+            Encoding::Cocoa => {
+                Some(scaled(input, NANO_PER_SEC)? + COCOA_SECONDS_FROM_UNIX * NANO_PER_SEC)
+            }
+            Encoding::Gps => decode_gps(scaled(input, NANO_PER_SEC)?),
+            Encoding::Ticks => Some(integer(input)? * 100 - TICKS_SECONDS_TO_UNIX * NANO_PER_SEC),
+            Encoding::Twitter => {
+                Some(((integer(input)? >> 22) + TWITTER_EPOCH_MILLIS) * NANO_PER_MILLI)
+            }
+            Encoding::Discord => {
+                Some(((integer(input)? >> 22) + DISCORD_EPOCH_MILLIS) * NANO_PER_MILLI)
+            }
+            Encoding::Uuid => decode_uuid(input),
+            Encoding::Ulid => decode_ulid(input),
+        }
+    }
+}
+
+/// A decimal number multiplied by `scale`, keeping up to nine digits of the
+/// fraction.
+fn scaled(input: &str, scale: i128) -> Option<i128> {
+    let value = DECIMAL.captures(input)?;
+    let whole = value["whole"].parse::<i128>().ok()?;
+    let fraction = match value.name("fraction") {
+        Some(fraction) => {
+            let fraction: String = fraction.as_str().chars().take(9).collect();
+            format!("{:0<9}", fraction).parse::<i128>().unwrap()
+        }
+        None => 0,
+    };
+
+    let scaled = whole.checked_mul(scale)? + fraction * scale / NANO_PER_SEC;
+    if value.name("sign").is_some() {
+        Some(-scaled)
+    } else {
+        Some(scaled)
+    }
+}
+
+/// A non-negative whole number, in decimal or `0x` hex.
+fn integer(input: &str) -> Option<i128> {
+    let value = if input.starts_with("0x") || input.starts_with("0X") {
+        i128::from_str_radix(&input[2..], 16).ok()?
+    } else {
+        input.parse::<i128>().ok()?
+    };
+
+    if value < 0 {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// NTP times are either `seconds.fraction`, or the 64 bit on-the-wire form
+/// with seconds in the top half and a binary fraction in the bottom half.
+fn decode_ntp(input: &str) -> Option<i128> {
+    let since_1900 = match integer(input) {
+        Some(value) if value > 0xffff_ffff => {
+            (value >> 32) * NANO_PER_SEC + (((value & 0xffff_ffff) * NANO_PER_SEC) >> 32)
+        }
+        _ => scaled(input, NANO_PER_SEC)?,
+    };
+
+    Some(since_1900 - NTP_SECONDS_TO_UNIX * NANO_PER_SEC)
+}
+
+fn decode_gps(nanos: i128) -> Option<i128> {
+    let mut unix = nanos + GPS_SECONDS_FROM_UNIX * NANO_PER_SEC;
+    for leap in LEAP_SECONDS.iter() {
+        if unix - NANO_PER_SEC >= leap * NANO_PER_SEC {
+            unix -= NANO_PER_SEC;
+        }
+    }
+
+    Some(unix)
+}
+
+/// Versions 1 and 6 count 100ns intervals since 1582, version 7 starts with
+/// Unix milliseconds. Other versions don't have a time in them.
+fn decode_uuid(input: &str) -> Option<i128> {
+    let value = UUID.captures(input)?;
+    let time_low = i128::from_str_radix(&value["time_low"], 16).ok()?;
+    let time_mid = i128::from_str_radix(&value["time_mid"], 16).ok()?;
+    let time_high = i128::from_str_radix(&value["time_high"], 16).ok()?;
+
+    let intervals = match &value["version"] {
+        "1" => (time_high << 48) | (time_mid << 32) | time_low,
+        "6" => (time_low << 28) | (time_mid << 12) | time_high,
+        "7" => return Some(((time_low << 16) | time_mid) * NANO_PER_MILLI),
+        _ => return None,
+    };
+
+    Some(intervals * 100 - GREGORIAN_SECONDS_TO_UNIX * NANO_PER_SEC)
+}
+
+/// The first ten characters of a ULID are Unix milliseconds in Crockford's
+/// base 32.
+fn decode_ulid(input: &str) -> Option<i128> {
+    if !ULID.is_match(input) {
+        return None;
+    }
+
+    let mut millis: i128 = 0;
+    for c in input.to_uppercase().chars().take(10) {
+        let digit = "0123456789ABCDEFGHJKMNPQRSTVWXYZ".find(c)?;
+        millis = millis * 32 + digit as i128;
+    }
+
+    Some(millis * NANO_PER_MILLI)
+}
+
+#[test]
+fn decode_samples() {
+    // 2018-12-04T04:20:22Z
+    let expected = 1_543_897_222 * NANO_PER_SEC;
+    let decode = |encoding: Encoding, input: &str| encoding.decode(input).unwrap();
+
+    // Nine digits of a day are only good to ~0.1ms.
+    assert_eq!(
+        expected,
+        (decode(Encoding::Excel, "43438.180810185186") + NANO_PER_SEC / 2) / NANO_PER_SEC
+            * NANO_PER_SEC
+    );
+    assert_eq!(expected, decode(Encoding::FileTime, "131883708220000000"));
+    assert_eq!(expected, decode(Encoding::FileTime, "0x1D48B88AC1D4700"));
+    assert_eq!(expected, decode(Encoding::Ntp, "3752886022"));
+    assert_eq!(expected, decode(Encoding::Ntp, "16118522730105536512"));
+    assert_eq!(expected, decode(Encoding::Cocoa, "565590022"));
+    assert_eq!(expected, decode(Encoding::Gps, "1227932440"));
+    assert_eq!(expected, decode(Encoding::Ticks, "636794940220000000"));
+    assert_eq!(
+        1_543_897_222_205 * NANO_PER_MILLI,
+        decode(Encoding::Twitter, "1069808605139566592")
+    );
+    assert_eq!(
+        1_543_897_222_205 * NANO_PER_MILLI,
+        decode(Encoding::Discord, "519367335681720320")
+    );
+    assert_eq!(
+        expected,
+        decode(Encoding::Uuid, "ea600700-f77b-11e8-8000-000000000000")
+    );
+    assert_eq!(
+        expected,
+        decode(Encoding::Uuid, "1e8f77be-a600-6700-8000-000000000000")
+    );
+    assert_eq!(
+        1_543_897_222_205 * NANO_PER_MILLI,
+        decode(Encoding::Uuid, "01677772-0c3d-7000-8000-000000000000")
+    );
+    assert_eq!(
+        1_543_897_222_205 * NANO_PER_MILLI,
+        decode(Encoding::Ulid, "01CXVQ431X0000000000000000")
+    );
+
+    assert_eq!(
+        None,
+        Encoding::Uuid.decode("9b2c1a8e-2f4b-4c3e-9d6a-1b2c3d4e5f60")
+    );
+    assert_eq!(
+        Some(Encoding::Uuid),
+        Encoding::detect("EA600700-F77B-11E8-8000-000000000000")
+    );
+    assert_eq!(
+        Some(Encoding::Ulid),
+        Encoding::detect("01CXVQ431X0000000000000000")
+    );
+    assert_eq!(None, Encoding::detect("1543897222"));
+    assert_eq!(None, Encoding::detect("00000000000000000000000000"));
+    assert_eq!(None, Encoding::detect("7ZZZZZZZZZZZZZZZZZZZZZZZZZ"));
+    assert_eq!(
+        Some(281_474_976_710_655 * NANO_PER_MILLI),
+        Encoding::Ulid.decode("7ZZZZZZZZZZZZZZZZZZZZZZZZZ")
+    );
+}
@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::fmt;
+
+/// Why some input couldn't be read as a time.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ParseErrorKind {
+    /// Nothing in the input looks like a date, time, zone or epoch.
+    UnknownFormat,
+    /// A date that doesn't exist, like `Feb 30 2019` or `2018-W54-1`.
+    InvalidDate,
+    /// A time of day that doesn't exist, like `25:00` or `13:30 pm`.
+    InvalidTime,
+    /// A weekday that none of the possible dates fall on, like the Monday in
+    /// `Mon Dec 4 2018`.
+    WrongWeekday,
+    /// A local time the clocks skip over in the zone it was written in.
+    SkippedTime,
+    /// A relative expression like `3 days ago` written alongside a date.
+    RelativeWithDate,
+    /// A time too far from 1970 to represent.
+    OutOfRange,
+    /// Input that isn't valid in the encoding it was read as, like a ULID
+    /// with the wrong number of characters.
+    InvalidEncoding,
+    /// Input with no fixed pattern, like `3 days ago` or an encoded ID.
+    NoPattern,
+}
+
+/// The part of the input that couldn't be read, and why.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    fragment: String,
+    message: String,
+}
+
+impl ParseError {
+    pub fn new<S: Into<String>>(kind: ParseErrorKind, fragment: &str, message: S) -> Self {
+        ParseError {
+            kind,
+            fragment: s!(fragment.trim()),
+            message: message.into(),
+        }
+    }
+
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// The text from the input that failed, like `Feb 30 2019` or `Mon`.
+    pub fn fragment(&self) -> &str {
+        &self.fragment
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for ParseError {}
@@ -0,0 +1,45 @@
+use super::parse::EpochUnit;
+
+/// One part of a written time, which each dialect spells its own way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Year,
+    ShortYear,
+    Month,
+    MonthUnpadded,
+    MonthAbbr,
+    MonthName,
+    Day,
+    DayUnpadded,
+    /// A day padded with a space, like syslog's `Dec  4`.
+    DaySpacePadded,
+    /// The `th` in `4th`.
+    DaySuffix,
+    DayOfYear,
+    WeekYear,
+    Week,
+    /// The ISO day of the week, 1 for Monday to 7 for Sunday.
+    WeekdayNumber,
+    WeekdayAbbr,
+    WeekdayName,
+    Hour,
+    HourUnpadded,
+    Hour12,
+    Hour12Unpadded,
+    Minute,
+    Second,
+    /// Fractional seconds with this many digits.
+    Fraction(usize),
+    AmPm,
+    /// `+0000`
+    Offset,
+    /// `+00:00`
+    OffsetColon,
+    /// `+00`
+    OffsetHours,
+    /// The `Z` in `04:20:22Z`.
+    Utc,
+    ZoneAbbr,
+    ZoneId,
+    Epoch(EpochUnit),
+}
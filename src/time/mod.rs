@@ -0,0 +1,35 @@
+//! The parser behind `toolkit time`. It reads times the way people write
+//! them, like `04/Dec/2018:04:20:22 +0000`, `Tue Dec 4 2018 4:20pm PST`,
+//! `next tuesday 9am` or `1543897222`, and gives back every reading of the
+//! input that makes sense.
+//!
+//! ```
+//! use toolkit::time::{self, DateRule, ParseErrorKind};
+//!
+//! let readings = time::parse("04/12/2018 04:20 UTC").unwrap().make_permutations();
+//! assert_eq!(Some(DateRule::MonthDayYear), readings[0].rule);
+//! assert_eq!("2018-04-12T04:20:00+00:00", readings[0].datetime.to_rfc3339());
+//!
+//! let error = time::parse("Feb 30 2019").unwrap_err();
+//! assert_eq!(ParseErrorKind::InvalidDate, error.kind());
+//! assert_eq!("Feb 30 2019", error.fragment());
+//! ```
+
+mod calendar;
+mod decode;
+mod error;
+mod field;
+mod parse;
+pub mod relative;
+mod zones;
+
+pub use self::calendar::Calendar;
+pub use self::decode::Encoding;
+pub use self::error::{ParseError, ParseErrorKind};
+pub use self::field::Field;
+pub use self::parse::{
+    choose_permutation, find_fields, find_times, parse, parse_with_base, parse_with_options,
+    CalendarDate, CalendarTime, DateRule, EpochSource, EpochTime, EpochUnit, ParseOptions,
+    ParsedZone, Permutation, StringTime, TimeResult,
+};
+pub use self::zones::{abbreviation_offset, find_abbreviation, ABBREVIATIONS};
@@ -0,0 +1,2449 @@
+use chrono::naive::{NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::{DateTime, Datelike, FixedOffset, LocalResult, Offset, TimeZone, Timelike, Weekday};
+use chrono_tz::Tz;
+use regex::{Captures, Regex};
+use std::cmp::Reverse;
+use std::convert::TryFrom;
+use std::ops::Range;
+use std::str::FromStr;
+
+use super::calendar::Calendar;
+use super::decode::Encoding;
+use super::error::{ParseError, ParseErrorKind};
+use super::field::Field;
+use super::relative::{self, RelativeTime};
+use super::zones;
+
+#[cfg(test)]
+use chrono::Duration;
+
+lazy_static! {
+    static ref CALENDAR_DAY: Regex = Regex::new(r"(?P<p1>\d{1,4})[-\\/](?P<p2>\d{1,4})[-\\/](?P<p3>\d{1,4})(?P<t>T)?").unwrap();
+    static ref TIME: Regex = Regex::new(r"(?i)(?P<hour>\d{1,2}):(?P<min>\d{2})([:\.](?P<sec>\d{2})?(\.(?P<nano>\d+))?)?( ?(?P<format>(.m)))?").unwrap();
+    // A time written as just the hour, like `9am` or `4 PM`.
+    static ref CLOCK_HOUR: Regex = Regex::new(r"(?i)\b(?P<hour>\d{1,2}) ?(?P<format>[ap]m)\b").unwrap();
+    static ref TIME_ZONE: Regex = Regex::new(r"(?P<zone>[\+-]\d{2}(:?\d{2})?)").unwrap();
+    static ref WEEKDAY: Regex = Regex::new(r"(?i)\b(?P<weekday>mon(day)?|tue(s(day)?)?|wed(nesday)?|thu(r(s(day)?)?)?|fri(day)?|sat(urday)?|sun(day)?)\b\.?,?").unwrap();
+    static ref NAMED_DATES: Vec<Regex> = vec![
+        // 4-Dec-2018, 4-Dec-18
+        Regex::new(&format!(r"(?i)\b(?P<day>\d{{1,2}})-(?P<month>{})-(?P<year>\d{{4}}|\d{{2}})\b", MONTH_NAMES)).unwrap(),
+        // 2018 Dec 4
+        Regex::new(&format!(r"(?i)\b(?P<year>\d{{4}})[\s/-]+(?P<month>{})\.?[\s/-]+(?P<day>\d{{1,2}})(st|nd|rd|th)?\b", MONTH_NAMES)).unwrap(),
+        // 4 December 2018, 4th of Dec
+        Regex::new(&format!(r"(?i)\b(?P<day>\d{{1,2}})(st|nd|rd|th)?[\s/-]+(of\s+)?(?P<month>{})\b\.?,?([\s/-]+(?P<year>\d{{4}})\b)?", MONTH_NAMES)).unwrap(),
+        // Dec 4 2018, December 4th, 2018
+        Regex::new(&format!(r"(?i)\b(?P<month>{})\.?[\s/-]+(?P<day>\d{{1,2}})(st|nd|rd|th)?\b,?([\s/-]+(?P<year>\d{{4}})\b)?", MONTH_NAMES)).unwrap(),
+    ];
+    static ref BASIC_FORMAT: Regex = Regex::new(r"^(?P<year>\d{4})(?P<month>\d{2})(?P<day>\d{2})(T(?P<hour>\d{2})(?P<min>\d{2})((?P<sec>\d{2})([\.,](?P<nano>\d+))?)?)?").unwrap();
+    static ref ISO_WEEK: Regex = Regex::new(r"\b(?P<year>\d{4})-?W(?P<week>\d{2})(-?(?P<day>[1-7]))?T?").unwrap();
+    static ref ISO_ORDINAL: Regex = Regex::new(r"\b(?P<year>\d{4})-(?P<ordinal>\d{3})(T|$|\s)").unwrap();
+    static ref FRACTIONAL_EPOCH: Regex =
+        Regex::new(r"^(?P<sign>-)?(?P<whole>\d+)\.(?P<fraction>\d+)$").unwrap();
+    static ref BARE_YEAR: Regex = Regex::new(r"(^|\s)(?P<year>\d{4})(\s|$)").unwrap();
+    // What can follow a date when scanning text, anchored to where it ends.
+    static ref SCAN_TIME: Regex = Regex::new(&format!(r"^(?:T|:|\s+)?{}", SCAN_CLOCK_PATTERN)).unwrap();
+    static ref SCAN_CLOCK: Regex = Regex::new(&format!(r"\b{}", SCAN_CLOCK_PATTERN)).unwrap();
+    static ref SCAN_ZONE: Regex = Regex::new(&format!(r"^(?:Z\b|\s*(?:{})\b|\s+[A-Za-z][A-Za-z_]*(?:/[A-Za-z_+\-0-9]+)*)", TIME_ZONE.as_str())).unwrap();
+    static ref SCAN_YEAR: Regex = Regex::new(r"^\s+\d{4}\b").unwrap();
+    static ref SCAN_EPOCH: Regex = Regex::new(r"\b\d{10}(?:\d{3}|\d{6}|\d{9})?(?:\.\d{1,9})?\b").unwrap();
+}
+
+const MONTH_NAMES: &str = "jan(uary)?|feb(ruary)?|mar(ch)?|apr(il)?|may|june?|july?|aug(ust)?|sep(t(ember)?)?|oct(ober)?|nov(ember)?|dec(ember)?";
+/// `TIME`, but only taking am/pm as a whole word so `4:20 America/New_York`
+/// keeps its zone.
+const SCAN_CLOCK_PATTERN: &str = r"\d{1,2}:\d{2}(?:[:\.]\d{2}(?:\.\d+)?)?(?i:\s?[ap]\.?m\.?\b)?";
+
+const SECONDS_MAX: i128 = 10_000_000_000;
+const NANO_PER_SEC: i128 = 1_000_000_000;
+/// Readings closer to 1970 than this aren't offered as alternatives; they're
+/// almost always a number in the wrong unit.
+const PLAUSIBLE_MIN_SECONDS: i64 = 30 * 86_400;
+/// Fewest digits before the point for a number like `1543897222.205` to be read
+/// as an epoch without `--unit`, so `10.30` and `1.5` aren't taken for 1970.
+const FRACTIONAL_EPOCH_DIGITS: usize = 9;
+
+/// What was read from a written time: the dates it could be, and the time and
+/// zone if it had them.
+#[derive(Debug, PartialEq)]
+pub struct StringTime {
+    dates: Vec<CalendarDate>,
+    time: Option<CalendarTime>,
+    timezone: Option<ParsedZone>,
+    /// The abbreviation the zone was written as, when it stands for more than
+    /// one offset and the first of them was picked.
+    ambiguous_zone: Option<String>,
+}
+
+/// The zone the input was written in. Named zones are kept as they are, since
+/// their offset depends on the local time being looked at.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ParsedZone {
+    Offset(FixedOffset),
+    Named(Tz),
+}
+
+impl ParsedZone {
+    /// Every instant `local` could mean in this zone: one normally, two when
+    /// the clocks go back over it, and none when they skip forward over it.
+    fn resolve(self, local: &NaiveDateTime) -> Vec<DateTime<FixedOffset>> {
+        match self {
+            ParsedZone::Offset(offset) => offset.from_local_datetime(local).single(),
+            ParsedZone::Named(zone) => match zone.from_local_datetime(local) {
+                LocalResult::Single(value) => Some(value.with_timezone(&value.offset().fix())),
+                LocalResult::Ambiguous(earliest, latest) => {
+                    return vec![
+                        earliest.with_timezone(&earliest.offset().fix()),
+                        latest.with_timezone(&latest.offset().fix()),
+                    ];
+                }
+                LocalResult::None => None,
+            },
+        }
+        .into_iter()
+        .collect()
+    }
+
+    fn named(self) -> Option<Tz> {
+        match self {
+            ParsedZone::Named(zone) => Some(zone),
+            ParsedZone::Offset(_) => None,
+        }
+    }
+}
+
+impl StringTime {
+    /// Every date the input could mean, like both `04/12/2018` readings.
+    pub fn dates(&self) -> &[CalendarDate] {
+        &self.dates
+    }
+
+    /// The time of day, if the input had one.
+    pub fn time(&self) -> Option<&CalendarTime> {
+        self.time.as_ref()
+    }
+
+    /// The zone the input was written in, if it had one.
+    pub fn zone(&self) -> Option<ParsedZone> {
+        self.timezone
+    }
+
+    fn is_empty(&self) -> bool {
+        self.dates.is_empty() && self.time.is_none() && self.timezone.is_none()
+    }
+
+    /// The first reading of the input in UTC, or `None` if it can't be placed.
+    pub fn to_utc_date_time(&self) -> Option<DateTime<chrono::Utc>> {
+        use chrono::Utc;
+
+        self.make_permutations()
+            .first()
+            .map(|x| x.datetime.with_timezone(&Utc))
+    }
+
+    /// The time of day, or the current time when the input didn't have one.
+    fn naive_time(&self) -> NaiveTime {
+        use chrono::Local;
+
+        self.time
+            .clone()
+            .map(|x| NaiveTime::from_hms_nano(x.hour, x.min, x.second, x.nano as u32))
+            .unwrap_or_else(|| Local::now().naive_local().time())
+    }
+
+    fn parsed_zone(&self) -> ParsedZone {
+        self.timezone
+            .unwrap_or_else(|| ParsedZone::Offset(FixedOffset::east(0)))
+    }
+
+    /// Every date the input could be on, or today in its zone when it didn't
+    /// have one, with a description of how it was read.
+    fn candidate_dates(&self) -> Vec<(NaiveDate, String, Option<DateRule>)> {
+        use chrono::Local;
+
+        let mut dates: Vec<(NaiveDate, String, Option<DateRule>)> = self
+            .dates
+            .iter()
+            .map(|x| {
+                (
+                    NaiveDate::from_ymd(x.year as i32, x.month, x.day),
+                    s!(x.rule.describe()),
+                    Some(x.rule),
+                )
+            })
+            .collect();
+
+        if dates.is_empty() {
+            let today = match self.parsed_zone() {
+                ParsedZone::Offset(offset) => {
+                    Local::now().with_timezone(&offset).date().naive_local()
+                }
+                ParsedZone::Named(zone) => Local::now().with_timezone(&zone).date().naive_local(),
+            };
+            dates.push((today, s!("no date given, using today"), None));
+        }
+
+        dates
+    }
+
+    pub fn make_permutations(&self) -> Vec<Permutation> {
+        let time = self.naive_time();
+        let timezone = self.parsed_zone();
+        let dates = self.candidate_dates();
+
+        let mut permutations = Vec::new();
+        for (date, interpretation, rule) in dates {
+            let interpretation = match (&self.ambiguous_zone, timezone) {
+                (Some(abbreviation), ParsedZone::Offset(offset)) => format!(
+                    "{}, reading the ambiguous {} as {}",
+                    interpretation, abbreviation, offset
+                ),
+                _ => interpretation,
+            };
+            let local = NaiveDateTime::new(date, time);
+            let resolved = timezone.resolve(&local);
+            let zone = timezone.named();
+
+            match resolved.as_slice() {
+                // `check_gaps` already turned away input where every reading
+                // is skipped over, or where one can't be moved past the gap,
+                // so this one is kept alongside the others.
+                [] => {
+                    if let Some(moved) = zone.and_then(|x| relative::from_local(&x, &local)) {
+                        let to = if moved.date().naive_local() == date {
+                            s!(moved.time())
+                        } else {
+                            s!(moved.naive_local())
+                        };
+                        permutations.push(Permutation {
+                            datetime: moved.with_timezone(&moved.offset().fix()),
+                            interpretation: format!(
+                                "{}, moved to {} because the clocks skip over {}",
+                                interpretation,
+                                to,
+                                local.time()
+                            ),
+                            rule,
+                            zone,
+                        });
+                    }
+                }
+                [datetime] => permutations.push(Permutation {
+                    datetime: *datetime,
+                    interpretation,
+                    rule,
+                    zone,
+                }),
+                [earliest, latest] => {
+                    permutations.push(Permutation {
+                        datetime: *earliest,
+                        interpretation: format!(
+                            "{}, at {} before the clocks go back",
+                            interpretation,
+                            earliest.offset()
+                        ),
+                        rule,
+                        zone,
+                    });
+                    permutations.push(Permutation {
+                        datetime: *latest,
+                        interpretation: format!(
+                            "{}, at {} after the clocks go back",
+                            interpretation,
+                            latest.offset()
+                        ),
+                        rule,
+                        zone,
+                    });
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        permutations
+    }
+
+    /// Local times that don't exist in the zone they were written in, because
+    /// the clocks skip over them. A skipped reading is kept as long as another
+    /// reading of the date did happen and it can be moved past the gap.
+    fn check_gaps(&self, written: &str) -> Result<(), ParseError> {
+        let zone = match (self.timezone, &self.time) {
+            (Some(ParsedZone::Named(zone)), Some(_)) => zone,
+            _ => return Ok(()),
+        };
+
+        let time = self.naive_time();
+        let skipped: Vec<NaiveDateTime> = self
+            .candidate_dates()
+            .into_iter()
+            .map(|(date, _, _)| NaiveDateTime::new(date, time))
+            .filter(|x| ParsedZone::Named(zone).resolve(x).is_empty())
+            .collect();
+
+        let unplaceable = skipped
+            .iter()
+            .any(|x| relative::from_local(&zone, x).is_none());
+        let missing = !self.dates.is_empty() && skipped.len() == self.dates.len();
+
+        if missing || unplaceable {
+            Err(ParseError::new(
+                ParseErrorKind::SkippedTime,
+                written,
+                format!(
+                    "{} doesn't exist in {}, the clocks skip over it",
+                    time,
+                    zone.name()
+                ),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// One way the input could be read, and a description of how it was read.
+#[derive(Debug, Clone)]
+pub struct Permutation {
+    pub datetime: DateTime<FixedOffset>,
+    pub interpretation: String,
+    pub rule: Option<DateRule>,
+    /// The named zone the input was in, if it had one.
+    pub zone: Option<Tz>,
+}
+
+/// Pick the single permutation that `rule` produced, falling back to the first
+/// one when the input couldn't be read that way (or only had one reading).
+pub fn choose_permutation(permutations: Vec<Permutation>, rule: DateRule) -> Vec<Permutation> {
+    let index = permutations
+        .iter()
+        .position(|x| x.rule == Some(rule))
+        .unwrap_or(0);
+
+    permutations.into_iter().skip(index).take(1).collect()
+}
+
+/// The result of parsing. An epoch can have more than one plausible unit, the
+/// first one is the unit its size suggests.
+#[derive(Debug, PartialEq)]
+pub enum TimeResult {
+    Epoch(Vec<EpochTime>),
+    String(StringTime),
+}
+
+impl TimeResult {
+    /// The first reading in UTC, or `None` if the input can't be placed.
+    pub fn to_utc_date_time(&self) -> Option<DateTime<chrono::Utc>> {
+        match self {
+            TimeResult::Epoch(epochs) => epochs.first().map(|x| x.to_utc_date_time()),
+            TimeResult::String(string_format) => string_format.to_utc_date_time(),
+        }
+    }
+
+    pub fn make_permutations(&self) -> Vec<Permutation> {
+        match self {
+            TimeResult::Epoch(epochs) => epochs.iter().map(|x| x.make_permutation()).collect(),
+            TimeResult::String(string_format) => string_format.make_permutations(),
+        }
+    }
+}
+
+/// The unit a Unix epoch is counted in.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum EpochUnit {
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+const EPOCH_UNITS: [EpochUnit; 4] = [
+    EpochUnit::Seconds,
+    EpochUnit::Milliseconds,
+    EpochUnit::Microseconds,
+    EpochUnit::Nanoseconds,
+];
+
+impl EpochUnit {
+    /// The unit named by `--unit`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "s" => Some(EpochUnit::Seconds),
+            "ms" => Some(EpochUnit::Milliseconds),
+            "us" => Some(EpochUnit::Microseconds),
+            "ns" => Some(EpochUnit::Nanoseconds),
+            _ => None,
+        }
+    }
+
+    /// Pick the unit from how big the number is: anything that would be past
+    /// the year 2286 in one unit is taken as the next smaller one.
+    fn from_magnitude(input: i128) -> Self {
+        let magnitude = input.abs();
+        if magnitude < SECONDS_MAX {
+            EpochUnit::Seconds
+        } else if magnitude < SECONDS_MAX * 1_000 {
+            EpochUnit::Milliseconds
+        } else if magnitude < SECONDS_MAX * 1_000_000 {
+            EpochUnit::Microseconds
+        } else {
+            EpochUnit::Nanoseconds
+        }
+    }
+
+    fn nanos(self) -> i128 {
+        match self {
+            EpochUnit::Seconds => 1_000_000_000,
+            EpochUnit::Milliseconds => 1_000_000,
+            EpochUnit::Microseconds => 1_000,
+            EpochUnit::Nanoseconds => 1,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            EpochUnit::Seconds => "Unix epoch in seconds",
+            EpochUnit::Milliseconds => "Unix epoch in milliseconds",
+            EpochUnit::Microseconds => "Unix epoch in microseconds",
+            EpochUnit::Nanoseconds => "Unix epoch in nanoseconds",
+        }
+    }
+}
+
+/// What a number was counted from: a Unix epoch in some unit, or one of the
+/// other systems in `decode`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum EpochSource {
+    Unix(EpochUnit),
+    Encoded(Encoding),
+}
+
+impl EpochSource {
+    fn describe(self) -> &'static str {
+        match self {
+            EpochSource::Unix(unit) => unit.describe(),
+            EpochSource::Encoded(encoding) => encoding.describe(),
+        }
+    }
+}
+
+/// An instant read from a number, like a Unix epoch or a ULID.
+#[derive(Debug, PartialEq)]
+pub struct EpochTime {
+    seconds: i64,
+    nanos: u32,
+    source: EpochSource,
+}
+
+impl EpochTime {
+    /// What the number was counted from.
+    pub fn source(&self) -> EpochSource {
+        self.source
+    }
+
+    /// The instant `nanos` nanoseconds from the epoch, if chrono can represent
+    /// it.
+    fn from_nanos(nanos: i128, source: EpochSource) -> Option<Self> {
+        let epoch = EpochTime {
+            seconds: i64::try_from(nanos.div_euclid(NANO_PER_SEC)).ok()?,
+            nanos: nanos.rem_euclid(NANO_PER_SEC) as u32,
+            source,
+        };
+
+        NaiveDateTime::from_timestamp_opt(epoch.seconds, epoch.nanos).map(|_| epoch)
+    }
+
+    /// Whether this reading is a time someone is likely to mean: between 1900
+    /// and 2200, and not within a month of 1970.
+    fn is_plausible(&self) -> bool {
+        let year = self.to_utc_date_time().year();
+        (1900..=2200).contains(&year) && self.seconds.abs() >= PLAUSIBLE_MIN_SECONDS
+    }
+
+    pub fn to_utc_date_time(&self) -> DateTime<chrono::Utc> {
+        use chrono::Utc;
+
+        DateTime::from_utc(NaiveDateTime::from_timestamp(self.seconds, self.nanos), Utc)
+    }
+
+    pub fn make_permutation(&self) -> Permutation {
+        Permutation {
+            datetime: self.to_utc_date_time().with_timezone(&FixedOffset::east(0)),
+            interpretation: s!(self.source.describe()),
+            rule: None,
+            zone: None,
+        }
+    }
+}
+
+/// Which reading of the input produced a date.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DateRule {
+    YearMonthDay,
+    YearDayMonth,
+    MonthDayYear,
+    DayMonthYear,
+    MonthName,
+    IsoWeek,
+    IsoOrdinal,
+    IsoBasic,
+    Relative,
+}
+
+impl DateRule {
+    pub fn describe(self) -> &'static str {
+        match self {
+            DateRule::YearMonthDay => "year/month/day",
+            DateRule::YearDayMonth => "year/day/month",
+            DateRule::MonthDayYear => "month/day/year",
+            DateRule::DayMonthYear => "day/month/year",
+            DateRule::MonthName => "named month",
+            DateRule::IsoWeek => "ISO 8601 week date",
+            DateRule::IsoOrdinal => "ISO 8601 ordinal date",
+            DateRule::IsoBasic => "ISO 8601 basic format",
+            DateRule::Relative => "relative to the base time",
+        }
+    }
+
+    /// The rule named by `--prefer`.
+    pub fn from_preference(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "mdy" => Some(DateRule::MonthDayYear),
+            "dmy" => Some(DateRule::DayMonthYear),
+            "ymd" => Some(DateRule::YearMonthDay),
+            _ => None,
+        }
+    }
+
+    /// The order dates are usually written in for a POSIX locale like
+    /// `en_US.UTF-8`. Most of the world writes day first, so that's the
+    /// fallback; `C` and `POSIX` get the ISO order.
+    pub fn from_locale(locale: &str) -> Self {
+        let locale = locale.split(&['.', '@'][..]).next().unwrap_or("");
+        if locale.is_empty() || locale == "C" || locale == "POSIX" {
+            return DateRule::YearMonthDay;
+        }
+
+        let mut parts = locale.split('_');
+        let language = parts.next().unwrap_or("");
+        let region = parts.next().unwrap_or("");
+
+        match (language, region) {
+            (_, "US") | (_, "PH") | (_, "BZ") | (_, "FM") | (_, "MH") | (_, "PW") => {
+                DateRule::MonthDayYear
+            }
+            ("zh", _)
+            | ("ja", _)
+            | ("ko", _)
+            | ("hu", _)
+            | ("lt", _)
+            | ("mn", _)
+            | ("sv", _)
+            | ("fa", _) => DateRule::YearMonthDay,
+            _ => DateRule::DayMonthYear,
+        }
+    }
+}
+
+/// A date read from the input, and which reading of it produced the date.
+#[derive(Debug, Clone)]
+pub struct CalendarDate {
+    year: u32,
+    month: u32,
+    day: u32,
+    rule: DateRule,
+}
+
+impl CalendarDate {
+    pub fn year(&self) -> i32 {
+        self.year as i32
+    }
+
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    pub fn rule(&self) -> DateRule {
+        self.rule
+    }
+
+    #[cfg(test)]
+    fn new(year: u32, month: u32, day: u32) -> Self {
+        CalendarDate::with_rule(year, month, day, DateRule::YearMonthDay)
+    }
+
+    fn with_rule(year: u32, month: u32, day: u32, rule: DateRule) -> Self {
+        CalendarDate {
+            year,
+            month,
+            day,
+            rule,
+        }
+    }
+
+    fn from_naive(date: NaiveDate, rule: DateRule) -> Self {
+        CalendarDate::with_rule(date.year() as u32, date.month(), date.day(), rule)
+    }
+}
+
+/// Two readings that land on the same day are the same date.
+impl PartialEq for CalendarDate {
+    fn eq(&self, other: &Self) -> bool {
+        self.year == other.year && self.month == other.month && self.day == other.day
+    }
+}
+
+/// A time of day read from the input.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CalendarTime {
+    hour: u32,
+    min: u32,
+    second: u32,
+    nano: u128,
+}
+
+impl CalendarTime {
+    pub fn hour(&self) -> u32 {
+        self.hour
+    }
+
+    pub fn minute(&self) -> u32 {
+        self.min
+    }
+
+    pub fn second(&self) -> u32 {
+        self.second
+    }
+
+    pub fn nanosecond(&self) -> u32 {
+        self.nano as u32
+    }
+
+    fn new(hour: u32, min: u32, second: u32, nano: u128) -> Self {
+        CalendarTime {
+            hour,
+            min,
+            second,
+            nano,
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<TimeResult, ParseError> {
+    use chrono::Local;
+
+    let now = Local::now();
+    parse_with_base(input, &now.with_timezone(&now.offset().fix()))
+}
+
+/// Parse `input`, resolving relative expressions like `3 days ago` or
+/// `next tuesday 9am` against `base`.
+pub fn parse_with_base(
+    input: &str,
+    base: &DateTime<FixedOffset>,
+) -> Result<TimeResult, ParseError> {
+    parse_with_options(input, base, ParseOptions::default())
+}
+
+/// How to read input that could mean more than one thing.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ParseOptions<'a> {
+    /// Read numbers as Unix epochs in this unit instead of guessing.
+    pub unit: Option<EpochUnit>,
+    /// Read the input as this encoding and nothing else.
+    pub encoding: Option<Encoding>,
+    /// Holidays for `+3 business days` to skip, besides weekends.
+    pub holidays: Option<&'a Calendar>,
+}
+
+/// Parse `input` like `parse_with_base`, following `options`.
+pub fn parse_with_options(
+    input: &str,
+    base: &DateTime<FixedOffset>,
+    options: ParseOptions,
+) -> Result<TimeResult, ParseError> {
+    let written = input;
+    let mut input = s!(input);
+    if let Some(encoding) = options.encoding.or_else(|| Encoding::detect(input.trim())) {
+        return parse_encoded(input.trim(), encoding);
+    }
+
+    // `20181204` is a number too, but as an epoch it'd be in 1970.
+    let basic = match options.unit {
+        Some(_) => None,
+        None => find_basic_format(input.trim()),
+    };
+
+    if basic.is_none() {
+        if let Some(value) = parse_whole(input.trim()) {
+            return parse_number(value, options.unit);
+        }
+
+        if let Some(value) = find_fractional_epoch(input.trim(), options.unit) {
+            return parse_fractional(&value, options.unit);
+        }
+    }
+
+    let mut string_time = StringTime {
+        dates: Vec::new(),
+        time: None,
+        timezone: None,
+        ambiguous_zone: None,
+    };
+
+    let relative = match relative::extract(&input) {
+        Some((relative, remaining)) => {
+            input = remaining;
+            Some(relative)
+        }
+        None => None,
+    };
+
+    let weekday = match WEEKDAY.captures(&input) {
+        Some(value) => {
+            let weekday = relative::parse_weekday(&value["weekday"].to_lowercase())
+                .map(|x| (x, s!(&value["weekday"])));
+            input = input.replace(&value[0], "");
+            weekday
+        }
+        None => None,
+    };
+
+    if let Some((matched, date, time)) = basic {
+        input = input.trim().replacen(&matched, "", 1);
+        string_time.time = time;
+        string_time
+            .dates
+            .push(CalendarDate::from_naive(date, DateRule::IsoBasic));
+    } else if let Some(value) = ISO_WEEK.captures(&input) {
+        let year = value["year"].parse::<i32>().unwrap();
+        let week = value["week"].parse::<u32>().unwrap();
+        let day = value
+            .name("day")
+            .map_or(1, |x| x.as_str().parse::<usize>().unwrap());
+        let weekday = [
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+            Weekday::Sat,
+            Weekday::Sun,
+        ][day - 1];
+
+        match NaiveDate::from_isoywd_opt(year, week, weekday) {
+            Some(date) => string_time
+                .dates
+                .push(CalendarDate::from_naive(date, DateRule::IsoWeek)),
+            None => {
+                return Err(ParseError::new(
+                    ParseErrorKind::InvalidDate,
+                    &value[0],
+                    format!("Invalid week date {}", &value[0]),
+                ));
+            }
+        }
+
+        input = input.replacen(&value[0], "", 1);
+    } else if let Some(value) = ISO_ORDINAL.captures(&input) {
+        let year = value["year"].parse::<i32>().unwrap();
+        let ordinal = value["ordinal"].parse::<u32>().unwrap();
+
+        match NaiveDate::from_yo_opt(year, ordinal) {
+            Some(date) => string_time
+                .dates
+                .push(CalendarDate::from_naive(date, DateRule::IsoOrdinal)),
+            None => {
+                return Err(ParseError::new(
+                    ParseErrorKind::InvalidDate,
+                    &value[0],
+                    format!("Invalid ordinal date {}", &value[0]),
+                ));
+            }
+        }
+
+        input = input.replacen(&value[0], " ", 1);
+    } else if let Some((matched, year, month, day)) = find_named_date(&input) {
+        input = input.replace(&matched, "");
+
+        let year = match year {
+            Some(year) => year,
+            None => match BARE_YEAR.captures(&input) {
+                Some(value) => {
+                    let year = value["year"].parse::<u32>().unwrap();
+                    input = input.replacen(&value["year"], "", 1);
+                    year
+                }
+                None => infer_year(base, month, day),
+            },
+        };
+
+        if NaiveDate::from_ymd_opt(year as i32, month, day).is_none() {
+            return Err(ParseError::new(
+                ParseErrorKind::InvalidDate,
+                &matched,
+                format!("Invalid date {}", matched),
+            ));
+        }
+
+        string_time.dates.push(CalendarDate::with_rule(
+            year,
+            month,
+            day,
+            DateRule::MonthName,
+        ));
+    } else if let Some(value) = CALENDAR_DAY.captures(&input) {
+        string_time.extract_dates(&value);
+
+        input = input.replace(CALENDAR_DAY.find(&input).unwrap().as_str(), "");
+    }
+
+    for try_tz in input.split(" ") {
+        let stripped = try_tz.replace("[", "").replace("]", "");
+        let stripped = stripped.as_str();
+        let matches = match zones::abbreviation_offset(stripped) {
+            Some((offset, ambiguous)) => {
+                if ambiguous {
+                    string_time.ambiguous_zone = Some(s!(stripped));
+                }
+                Some(ParsedZone::Offset(offset))
+            }
+            None => Tz::from_str(stripped).ok().map(ParsedZone::Named),
+        };
+
+        if let Some(parsed_tz) = matches {
+            string_time.timezone = Some(parsed_tz);
+            input = input.replace(try_tz, "");
+            break;
+        }
+    }
+
+    let mut written_time = String::new();
+    if let Some(value) = TIME.captures(&input) {
+        string_time.extract_time(&value)?;
+        written_time = s!(&value[0]);
+
+        input = input.replace(TIME.find(&input).unwrap().as_str(), "");
+    } else if let Some(value) = CLOCK_HOUR.captures(&input) {
+        string_time.extract_time(&value)?;
+        written_time = s!(&value[0]);
+
+        input = input.replace(CLOCK_HOUR.find(&input).unwrap().as_str(), "");
+    }
+
+    if let Some(value) = TIME_ZONE.captures(&input) {
+        string_time.extract_time_zone(&value);
+
+        input = input.replace(TIME_ZONE.find(&input).unwrap().as_str(), "");
+    }
+
+    if let Some((weekday, written_weekday)) = weekday {
+        string_time.check_weekday(weekday, &written_weekday)?;
+    }
+
+    if let Some(relative) = relative {
+        let weekends = Calendar::default();
+        let holidays = options.holidays.unwrap_or(&weekends);
+        return string_time.resolve_relative(&relative, base, holidays, written);
+    }
+
+    string_time.check_gaps(&written_time)?;
+
+    if string_time.is_empty() {
+        Err(ParseError::new(
+            ParseErrorKind::UnknownFormat,
+            &input,
+            format!("Unknown format {}", input),
+        ))
+    } else {
+        Ok(TimeResult::String(string_time))
+    }
+}
+
+/// Every time in `text` the parser understands, like the timestamps in a log
+/// line, with the byte range it covers. Candidates are a date with whatever
+/// time, zone and year follow it, a time with a zone, or an epoch; each is
+/// read longest first, and the first reading that parses wins.
+pub fn find_times(
+    text: &str,
+    base: &DateTime<FixedOffset>,
+    options: ParseOptions,
+) -> Vec<(Range<usize>, TimeResult)> {
+    let mut candidates = candidate_spans(text);
+    candidates.sort_by_key(|(start, ends)| (*start, Reverse(ends[0])));
+
+    let mut found: Vec<(Range<usize>, TimeResult)> = Vec::new();
+    for (start, ends) in candidates {
+        if found.iter().any(|(range, _)| range.end > start) {
+            continue;
+        }
+
+        for end in ends {
+            if let Ok(result) = parse_with_options(&text[start..end], base, options) {
+                if !result.make_permutations().is_empty() {
+                    found.push((start..end, result));
+                    break;
+                }
+            }
+        }
+    }
+
+    found
+}
+
+/// Where each candidate starts, and every place it could end, longest first.
+fn candidate_spans(text: &str) -> Vec<(usize, Vec<usize>)> {
+    let mut candidates = Vec::new();
+
+    let dates = CALENDAR_DAY
+        .find_iter(text)
+        .chain(NAMED_DATES.iter().flat_map(|x| x.find_iter(text)));
+    for date in dates {
+        if follows_word(text, date.start()) {
+            continue;
+        }
+
+        let start = match WEEKDAY.find_iter(text[..date.start()].trim_end()).last() {
+            Some(weekday) if weekday.end() == text[..date.start()].trim_end().len() => {
+                weekday.start()
+            }
+            _ => date.start(),
+        };
+
+        // A date on its own needs a 4 digit year, so `1/2/3` or `may 5` in
+        // prose are left alone.
+        let mut ends = scan_extensions(text, date.end());
+        if has_four_digit_year(date.as_str()) {
+            ends.push(date.end());
+        }
+
+        if !ends.is_empty() {
+            candidates.push((start, ends));
+        }
+    }
+
+    // A time without a date is only worth converting when it has a zone.
+    for time in SCAN_CLOCK.find_iter(text) {
+        if let Some(end) = scan_zone(text, time.end()) {
+            candidates.push((time.start(), vec![end]));
+        }
+    }
+
+    for epoch in SCAN_EPOCH.find_iter(text) {
+        candidates.push((epoch.start(), vec![epoch.end()]));
+    }
+
+    candidates
+}
+
+/// The ends of the time, zone and year after a date ending at `from`, longest
+/// first.
+fn scan_extensions(text: &str, from: usize) -> Vec<usize> {
+    let time = SCAN_TIME.find(&text[from..]).map(|x| from + x.end());
+    let zone = scan_zone(text, time.unwrap_or(from));
+    let last = zone.or(time).unwrap_or(from);
+    let year = SCAN_YEAR.find(&text[last..]).map(|x| last + x.end());
+
+    let mut ends: Vec<usize> = vec![year, zone, time].into_iter().flatten().collect();
+    ends.dedup();
+    ends
+}
+
+/// The end of a zone right after `from`: an offset, `Z`, an abbreviation like
+/// `PST` or a name like `America/New_York`.
+fn scan_zone(text: &str, from: usize) -> Option<usize> {
+    let found = SCAN_ZONE.find(&text[from..])?;
+    let word = found.as_str().trim();
+    let known = word == "Z"
+        || word.starts_with('+')
+        || word.starts_with('-')
+        || zones::find_abbreviation(word).is_some()
+        || Tz::from_str(word).is_ok();
+
+    if known {
+        Some(from + found.end())
+    } else {
+        None
+    }
+}
+
+fn follows_word(text: &str, index: usize) -> bool {
+    match text[..index].chars().next_back() {
+        Some(x) => x.is_alphanumeric(),
+        None => false,
+    }
+}
+
+fn has_four_digit_year(date: &str) -> bool {
+    date.split(|x: char| !x.is_ascii_digit())
+        .any(|x| x.len() == 4)
+}
+
+/// Where each part of a time is in `input`, read the way `parse_with_options`
+/// reads it. `rule` picks which of the readings of a date like `04/12/2018`
+/// to describe.
+pub fn find_fields(
+    input: &str,
+    rule: Option<DateRule>,
+    options: ParseOptions,
+) -> Result<Vec<(Range<usize>, Field)>, ParseError> {
+    let offset = input.len() - input.trim_start().len();
+    let trimmed = input.trim();
+    if options
+        .encoding
+        .or_else(|| Encoding::detect(trimmed))
+        .is_some()
+    {
+        return Err(ParseError::new(
+            ParseErrorKind::NoPattern,
+            trimmed,
+            format!("`{}` is an encoded ID, not a written time", trimmed),
+        ));
+    }
+
+    let basic = match options.unit {
+        Some(_) => None,
+        None => find_basic_format(trimmed).and_then(|_| BASIC_FORMAT.captures(trimmed)),
+    };
+
+    if basic.is_none() {
+        if let Some(value) = parse_whole(trimmed) {
+            let unit = options
+                .unit
+                .unwrap_or_else(|| EpochUnit::from_magnitude(value));
+            return Ok(vec![(offset..offset + trimmed.len(), Field::Epoch(unit))]);
+        }
+
+        if let Some(value) = find_fractional_epoch(trimmed, options.unit) {
+            let whole = value.name("whole").unwrap();
+            let unit = options.unit.unwrap_or_else(|| {
+                EpochUnit::from_magnitude(parse_whole(whole.as_str()).unwrap_or(0))
+            });
+            let fraction = value.name("fraction").unwrap();
+            let start = offset + value.name("sign").map_or(whole.start(), |x| x.start());
+            return Ok(vec![
+                (start..offset + whole.end(), Field::Epoch(unit)),
+                (
+                    offset + fraction.start()..offset + fraction.end(),
+                    Field::Fraction(fraction.as_str().len()),
+                ),
+            ]);
+        }
+    }
+
+    if relative::extract(input).is_some() {
+        return Err(ParseError::new(
+            ParseErrorKind::NoPattern,
+            trimmed,
+            format!(
+                "`{}` is relative to now, so it has no fixed pattern",
+                trimmed
+            ),
+        ));
+    }
+
+    // Found parts are blanked out, the way the parser removes them, but
+    // keeping everything else where it was.
+    let mut text = s!(input);
+    let mut fields = Vec::new();
+    let mut claim = |text: &mut String, range: Range<usize>, field: Field| {
+        text.replace_range(range.clone(), &" ".repeat(range.len()));
+        fields.push((range, field));
+    };
+
+    if let Some(value) = WEEKDAY.captures(input) {
+        let weekday = value.name("weekday").unwrap();
+        let field = if weekday.as_str().len() == 3 {
+            Field::WeekdayAbbr
+        } else {
+            Field::WeekdayName
+        };
+        claim(&mut text, weekday.range(), field);
+    }
+
+    let current = text.clone();
+    if let Some(value) = basic {
+        let parts = [
+            ("year", Field::Year),
+            ("month", Field::Month),
+            ("day", Field::Day),
+            ("hour", Field::Hour),
+            ("min", Field::Minute),
+            ("sec", Field::Second),
+        ];
+        for (name, field) in parts.iter() {
+            if let Some(part) = value.name(name) {
+                claim(
+                    &mut text,
+                    offset + part.start()..offset + part.end(),
+                    *field,
+                );
+            }
+        }
+        if let Some(nano) = value.name("nano") {
+            let range = offset + nano.start()..offset + nano.end();
+            claim(&mut text, range, Field::Fraction(nano.as_str().len()));
+        }
+    } else if let Some(value) = ISO_WEEK.captures(&current) {
+        claim(
+            &mut text,
+            value.name("year").unwrap().range(),
+            Field::WeekYear,
+        );
+        claim(&mut text, value.name("week").unwrap().range(), Field::Week);
+        if let Some(day) = value.name("day") {
+            claim(&mut text, day.range(), Field::WeekdayNumber);
+        }
+    } else if let Some(value) = ISO_ORDINAL.captures(&current) {
+        claim(&mut text, value.name("year").unwrap().range(), Field::Year);
+        claim(
+            &mut text,
+            value.name("ordinal").unwrap().range(),
+            Field::DayOfYear,
+        );
+    } else if let Some(value) = NAMED_DATES.iter().find_map(|x| x.captures(&current)) {
+        let day = value.name("day").unwrap();
+        if day.as_str().len() == 1 && input[..day.start()].ends_with("  ") {
+            claim(&mut text, day.start() - 1..day.end(), Field::DaySpacePadded);
+        } else {
+            claim(&mut text, day.range(), day_field(day.as_str()));
+        }
+        let suffix: String = input[day.end()..].chars().take(2).collect();
+        if ["st", "nd", "rd", "th"].contains(&suffix.to_lowercase().as_str()) {
+            claim(&mut text, day.end()..day.end() + 2, Field::DaySuffix);
+        }
+
+        let month = value.name("month").unwrap();
+        let field = if month.as_str().len() == 3 {
+            Field::MonthAbbr
+        } else {
+            Field::MonthName
+        };
+        claim(&mut text, month.range(), field);
+
+        match value.name("year") {
+            Some(year) => claim(&mut text, year.range(), year_field(year.as_str())),
+            None => {
+                if let Some(year) = BARE_YEAR
+                    .captures(&text.clone())
+                    .and_then(|x| x.name("year"))
+                {
+                    claim(&mut text, year.range(), Field::Year);
+                }
+            }
+        }
+    } else if let Some(value) = CALENDAR_DAY.captures(&current) {
+        let p1 = value.name("p1").unwrap();
+        let p2 = value.name("p2").unwrap();
+        let p3 = value.name("p3").unwrap();
+
+        let rule = match rule {
+            _ if value.name("t").is_some() => DateRule::YearMonthDay,
+            Some(rule @ DateRule::YearDayMonth)
+            | Some(rule @ DateRule::MonthDayYear)
+            | Some(rule @ DateRule::DayMonthYear) => rule,
+            _ if p1.as_str().len() == 4 => DateRule::YearMonthDay,
+            _ => DateRule::MonthDayYear,
+        };
+        let (year, month, day) = match rule {
+            DateRule::YearDayMonth => (p1, p3, p2),
+            DateRule::MonthDayYear => (p3, p1, p2),
+            DateRule::DayMonthYear => (p3, p2, p1),
+            _ => (p1, p2, p3),
+        };
+
+        claim(&mut text, year.range(), year_field(year.as_str()));
+        let field = if month.as_str().len() == 1 {
+            Field::MonthUnpadded
+        } else {
+            Field::Month
+        };
+        claim(&mut text, month.range(), field);
+        claim(&mut text, day.range(), day_field(day.as_str()));
+    }
+
+    let mut start = 0;
+    for word in text.clone().split(' ') {
+        let stripped = word.trim_start_matches('[').trim_end_matches(']');
+        let field = if zones::find_abbreviation(stripped).is_some() {
+            Some(Field::ZoneAbbr)
+        } else if Tz::from_str(stripped).is_ok() {
+            Some(Field::ZoneId)
+        } else {
+            None
+        };
+
+        if let Some(field) = field {
+            let at = start + word.find(stripped).unwrap();
+            claim(&mut text, at..at + stripped.len(), field);
+            break;
+        }
+        start += word.len() + 1;
+    }
+
+    if let Some(value) = TIME.captures(&text.clone()) {
+        let twelve_hour = value.name("format").is_some();
+        let hour = value.name("hour").unwrap();
+        let field = match (twelve_hour, hour.as_str().len()) {
+            (true, 1) => Field::Hour12Unpadded,
+            (true, _) => Field::Hour12,
+            (false, 1) => Field::HourUnpadded,
+            (false, _) => Field::Hour,
+        };
+        claim(&mut text, hour.range(), field);
+        claim(&mut text, value.name("min").unwrap().range(), Field::Minute);
+        if let Some(second) = value.name("sec") {
+            claim(&mut text, second.range(), Field::Second);
+        }
+        if let Some(nano) = value.name("nano") {
+            claim(
+                &mut text,
+                nano.range(),
+                Field::Fraction(nano.as_str().len()),
+            );
+        }
+        if let Some(format) = value.name("format") {
+            claim(&mut text, format.range(), Field::AmPm);
+        }
+
+        let end = value.get(0).unwrap().end();
+        if input[end..].starts_with('Z') {
+            claim(&mut text, end..end + 1, Field::Utc);
+        }
+    }
+
+    if let Some(value) = TIME_ZONE.captures(&text.clone()) {
+        let zone = value.name("zone").unwrap();
+        let field = if zone.as_str().contains(':') {
+            Field::OffsetColon
+        } else if zone.as_str().len() == 3 {
+            Field::OffsetHours
+        } else {
+            Field::Offset
+        };
+        claim(&mut text, zone.range(), field);
+    }
+
+    if fields.is_empty() {
+        return Err(ParseError::new(
+            ParseErrorKind::UnknownFormat,
+            trimmed,
+            format!("Unknown format {}", trimmed),
+        ));
+    }
+
+    fields.sort_by_key(|(range, _)| range.start);
+    Ok(fields)
+}
+
+fn year_field(year: &str) -> Field {
+    if year.len() == 2 {
+        Field::ShortYear
+    } else {
+        Field::Year
+    }
+}
+
+fn day_field(day: &str) -> Field {
+    if day.len() == 1 {
+        Field::DayUnpadded
+    } else {
+        Field::Day
+    }
+}
+
+/// Find an ISO 8601 basic format date like `20181204` or `20181204T042022Z`
+/// at the start of `input`, returning the matched text, the date and the time
+/// if there was one.
+fn find_basic_format(input: &str) -> Option<(String, NaiveDate, Option<CalendarTime>)> {
+    let value = BASIC_FORMAT.captures(input)?;
+    if input[value[0].len()..].starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let part = |name: &str| value.name(name).map(|x| x.as_str().parse::<u32>().unwrap());
+
+    let date = NaiveDate::from_ymd_opt(part("year")? as i32, part("month")?, part("day")?)?;
+    let time = match (part("hour"), part("min")) {
+        (Some(hour), Some(min)) => {
+            let second = part("sec").unwrap_or(0);
+            NaiveTime::from_hms_opt(hour, min, second)?;
+
+            let nano = value.name("nano").map_or(0, |x| {
+                let digits: String = x.as_str().chars().take(9).collect();
+                format!("{:0<9}", digits).parse::<u128>().unwrap()
+            });
+            Some(CalendarTime::new(hour, min, second, nano))
+        }
+        _ => None,
+    };
+
+    Some((s!(&value[0]), date, time))
+}
+
+/// Find a date with a month name in it, returning the matched text and the
+/// year (if it was part of the date), month and day.
+fn find_named_date(input: &str) -> Option<(String, Option<u32>, u32, u32)> {
+    for pattern in NAMED_DATES.iter() {
+        if let Some(value) = pattern.captures(input) {
+            let month = month_number(&value["month"])?;
+            let day = value["day"].parse::<u32>().unwrap();
+            let year = value.name("year").map(|x| {
+                let year = x.as_str().parse::<u32>().unwrap();
+                if year < 100 {
+                    year + 2000
+                } else {
+                    year
+                }
+            });
+
+            return Some((s!(&value[0]), year, month, day));
+        }
+    }
+
+    None
+}
+
+/// The year for a date written without one, like syslog's `Dec  4 04:20:22`.
+/// Logs are about the past, so it's the latest `month`/`day` that isn't more
+/// than a day after `base`.
+fn infer_year(base: &DateTime<FixedOffset>, month: u32, day: u32) -> u32 {
+    let year = base.year();
+    let latest = base.naive_local().date().succ();
+    match NaiveDate::from_ymd_opt(year, month, day) {
+        Some(date) if date > latest => (year - 1) as u32,
+        // Feb 29 in a year without one; the last year that had it.
+        None if month == 2 && day == 29 => (year - 4..year)
+            .rev()
+            .find(|x| NaiveDate::from_ymd_opt(*x, 2, 29).is_some())
+            .unwrap_or(year) as u32,
+        _ => year as u32,
+    }
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    let month = match &name.to_lowercase()[..3] {
+        "jan" => 1,
+        "feb" => 2,
+        "mar" => 3,
+        "apr" => 4,
+        "may" => 5,
+        "jun" => 6,
+        "jul" => 7,
+        "aug" => 8,
+        "sep" => 9,
+        "oct" => 10,
+        "nov" => 11,
+        "dec" => 12,
+        _ => return None,
+    };
+
+    Some(month)
+}
+
+impl StringTime {
+    /// Drop the candidate dates that don't fall on `weekday`. It's an error if
+    /// none of them do.
+    fn check_weekday(&mut self, weekday: Weekday, written: &str) -> Result<(), ParseError> {
+        if self.dates.is_empty() {
+            return Ok(());
+        }
+
+        let matching: Vec<CalendarDate> = self
+            .dates
+            .iter()
+            .filter(|x| NaiveDate::from_ymd(x.year as i32, x.month, x.day).weekday() == weekday)
+            .cloned()
+            .collect();
+
+        if matching.is_empty() {
+            return Err(ParseError::new(
+                ParseErrorKind::WrongWeekday,
+                written,
+                format!(
+                    "None of the possible dates fall on a {:?}: {:?}",
+                    weekday, self.dates
+                ),
+            ));
+        }
+
+        self.dates = matching;
+        Ok(())
+    }
+
+    fn resolve_relative(
+        mut self,
+        relative: &RelativeTime,
+        base: &DateTime<FixedOffset>,
+        holidays: &Calendar,
+        written: &str,
+    ) -> Result<TimeResult, ParseError> {
+        use chrono::naive::NaiveTime;
+
+        if !self.dates.is_empty() {
+            return Err(ParseError::new(
+                ParseErrorKind::RelativeWithDate,
+                written,
+                "A relative expression can not be combined with a date",
+            ));
+        }
+
+        let time = self
+            .time
+            .clone()
+            .map(|x| NaiveTime::from_hms_nano(x.hour, x.min, x.second, x.nano as u32));
+        let zone = self
+            .timezone
+            .unwrap_or_else(|| ParsedZone::Offset(*base.offset()));
+
+        let resolved = match zone {
+            ParsedZone::Offset(offset) => relative.resolve(base, time, &offset, holidays),
+            ParsedZone::Named(named) => relative
+                .resolve(base, time, &named, holidays)
+                .map(|x| x.with_timezone(&x.offset().fix())),
+        };
+        let resolved = match resolved {
+            Some(resolved) => resolved,
+            None => {
+                return Err(ParseError::new(
+                    ParseErrorKind::OutOfRange,
+                    written,
+                    "Relative expression is out of range",
+                ));
+            }
+        };
+
+        self.dates.push(CalendarDate::with_rule(
+            resolved.year() as u32,
+            resolved.month(),
+            resolved.day(),
+            DateRule::Relative,
+        ));
+        self.time = Some(CalendarTime::new(
+            resolved.hour(),
+            resolved.minute(),
+            resolved.second(),
+            resolved.nanosecond() as u128,
+        ));
+        self.timezone = Some(zone);
+
+        Ok(TimeResult::String(self))
+    }
+
+    fn extract_time_zone(&mut self, value: &Captures) {
+        let zone_str = value.name("zone").unwrap().as_str().replace(":", "");
+        let zone = &zone_str[1..];
+
+        let mut zone_offset = if zone.len() == 2 {
+            zone.parse::<i32>().unwrap() * 100
+        } else {
+            zone.parse::<i32>().unwrap()
+        };
+
+        if zone_str.chars().nth(0).unwrap() == '-' {
+            zone_offset = -1 * zone_offset;
+        }
+
+        let seconds_offset = (zone_offset % 100) * 60 + (zone_offset / 100) * 60 * 60;
+
+        self.timezone = Some(ParsedZone::Offset(FixedOffset::east(seconds_offset)));
+    }
+
+    fn extract_time(&mut self, value: &Captures) -> Result<(), ParseError> {
+        let hour = value.name("hour").unwrap().as_str().parse::<u32>().unwrap();
+        let min = value
+            .name("min")
+            .map_or(0, |x| x.as_str().parse::<u32>().unwrap());
+        let second = value
+            .name("sec")
+            .map_or(0, |x| x.as_str().parse::<u32>().unwrap());
+        let (length, sub_sec) = value.name("nano").map_or((0, 0), |x| {
+            (x.as_str().len(), x.as_str().parse::<u128>().unwrap())
+        });
+
+        // 12am is midnight and 12pm is noon, and there's no 0am or 13pm.
+        let format = value.name("format").map(|x| x.as_str().to_lowercase());
+        let hour = match (format.as_deref(), hour) {
+            (Some("am"), 12) => Some(0),
+            (Some("am"), 1..=11) | (Some("pm"), 12) => Some(hour),
+            (Some("pm"), 1..=11) => Some(hour + 12),
+            (Some("am"), _) | (Some("pm"), _) => None,
+            _ => Some(hour),
+        };
+
+        let nano = match length {
+            3 => sub_sec * 10u128.pow(6),
+            6 => sub_sec * 10u128.pow(3),
+            9 => sub_sec,
+            _ => 0,
+        };
+
+        let hour = match hour {
+            Some(hour)
+                if NaiveTime::from_hms_nano_opt(hour, min, second, nano as u32).is_some() =>
+            {
+                hour
+            }
+            _ => {
+                return Err(ParseError::new(
+                    ParseErrorKind::InvalidTime,
+                    &value[0],
+                    format!("Invalid time {}", &value[0]),
+                ));
+            }
+        };
+
+        self.time = Some(CalendarTime::new(hour, min, second, nano));
+        Ok(())
+    }
+
+    fn extract_dates(&mut self, value: &Captures) {
+        let part1 = value.name("p1").unwrap().as_str().parse::<u32>().unwrap();
+        let part2 = value.name("p2").unwrap().as_str().parse::<u32>().unwrap();
+        let part3 = value.name("p3").unwrap().as_str().parse::<u32>().unwrap();
+
+        let year = if part1 < 100 { part1 + 2000 } else { part1 };
+
+        if value.name("t").is_some() {
+            if let Some(_) = NaiveDate::from_ymd_opt(year as i32, part2, part3) {
+                self.dates.push(CalendarDate {
+                    year,
+                    month: part2,
+                    day: part3,
+                    rule: DateRule::YearMonthDay,
+                });
+            }
+            return;
+        }
+
+        if let Some(_) = NaiveDate::from_ymd_opt(year as i32, part3, part2) {
+            self.dates.push(CalendarDate {
+                year,
+                month: part3,
+                day: part2,
+                rule: DateRule::YearDayMonth,
+            });
+        }
+
+        if let Some(_) = NaiveDate::from_ymd_opt(year as i32, part2, part3) {
+            self.dates.push(CalendarDate {
+                year,
+                month: part2,
+                day: part3,
+                rule: DateRule::YearMonthDay,
+            });
+        }
+
+        let year = if part3 < 100 { part3 + 2000 } else { part3 };
+
+        if let Some(_) = NaiveDate::from_ymd_opt(year as i32, part1, part2) {
+            self.dates.push(CalendarDate {
+                year: year,
+                month: part1,
+                day: part2,
+                rule: DateRule::MonthDayYear,
+            });
+        }
+
+        if let Some(_) = NaiveDate::from_ymd_opt(year as i32, part2, part1) {
+            self.dates.push(CalendarDate {
+                year: year,
+                month: part2,
+                day: part1,
+                rule: DateRule::DayMonthYear,
+            });
+        }
+    }
+}
+
+/// A whole number, anywhere from `i64::MIN` up to `u64::MAX` so nanosecond
+/// epochs after 2262 still fit.
+fn parse_whole(input: &str) -> Option<i128> {
+    input
+        .parse::<i64>()
+        .map(i128::from)
+        .or_else(|_| input.parse::<u64>().map(i128::from))
+        .ok()
+}
+
+/// `input` as a number with a fraction, when it has enough digits before the
+/// point to be an epoch (or `unit` says it is one).
+fn find_fractional_epoch(input: &str, unit: Option<EpochUnit>) -> Option<Captures<'_>> {
+    FRACTIONAL_EPOCH
+        .captures(input)
+        .filter(|x| unit.is_some() || x["whole"].len() >= FRACTIONAL_EPOCH_DIGITS)
+}
+
+/// Read a whole number as a Unix epoch. Without a `unit`, the size of the
+/// number picks the unit, and any other unit that gives a plausible time is
+/// offered after it. Negative numbers are before 1970.
+fn parse_number(input: i128, unit: Option<EpochUnit>) -> Result<TimeResult, ParseError> {
+    let picked = unit.unwrap_or_else(|| EpochUnit::from_magnitude(input));
+
+    let picked_epoch = EpochTime::from_nanos(input * picked.nanos(), EpochSource::Unix(picked));
+    let mut epochs = match picked_epoch {
+        Some(epoch) => vec![epoch],
+        None => {
+            return Err(ParseError::new(
+                ParseErrorKind::OutOfRange,
+                &input.to_string(),
+                format!("Epoch out of range {}", input),
+            ));
+        }
+    };
+
+    if unit.is_none() {
+        epochs.extend(
+            EPOCH_UNITS
+                .iter()
+                .filter(|x| **x != picked)
+                .filter_map(|x| EpochTime::from_nanos(input * x.nanos(), EpochSource::Unix(*x)))
+                .filter(|x| x.is_plausible()),
+        );
+    }
+
+    Ok(TimeResult::Epoch(epochs))
+}
+
+/// Read `1543897222.205` as an epoch in `unit`, or the unit the size of the
+/// whole part suggests, keeping up to nanosecond precision from the fraction.
+fn parse_fractional(value: &Captures, unit: Option<EpochUnit>) -> Result<TimeResult, ParseError> {
+    let whole = match parse_whole(&value["whole"]) {
+        Some(whole) => whole,
+        None => {
+            return Err(ParseError::new(
+                ParseErrorKind::OutOfRange,
+                &value[0],
+                format!("Unknown number {}", &value[0]),
+            ));
+        }
+    };
+
+    let unit = unit.unwrap_or_else(|| EpochUnit::from_magnitude(whole));
+    let fraction: String = value["fraction"].chars().take(9).collect();
+    let fraction = format!("{:0<9}", fraction).parse::<i128>().unwrap();
+
+    let mut nanos = whole * unit.nanos() + fraction * unit.nanos() / NANO_PER_SEC;
+    if value.name("sign").is_some() {
+        nanos = -nanos;
+    }
+
+    match EpochTime::from_nanos(nanos, EpochSource::Unix(unit)) {
+        Some(epoch) => Ok(TimeResult::Epoch(vec![epoch])),
+        None => Err(ParseError::new(
+            ParseErrorKind::OutOfRange,
+            &value[0],
+            format!("Epoch out of range {}", &value[0]),
+        )),
+    }
+}
+
+fn parse_encoded(input: &str, encoding: Encoding) -> Result<TimeResult, ParseError> {
+    match encoding
+        .decode(input)
+        .and_then(|x| EpochTime::from_nanos(x, EpochSource::Encoded(encoding)))
+    {
+        Some(epoch) => Ok(TimeResult::Epoch(vec![epoch])),
+        None => Err(ParseError::new(
+            ParseErrorKind::InvalidEncoding,
+            input,
+            format!("`{}` isn't a {}", input, encoding.describe()),
+        )),
+    }
+}
+
+#[cfg(test)]
+fn assert_contains_date(time_results: &TimeResult, required: &[CalendarDate]) {
+    let string_time = match time_results {
+        TimeResult::String(string_time) => string_time,
+        _ => panic!("Was epoch"),
+    };
+
+    let mut dates = string_time.dates.clone();
+
+    for ut in required {
+        assert!(
+            dates.contains(ut),
+            "{:?} was not found in results: {:?}",
+            ut,
+            dates
+        );
+        dates
+            .iter()
+            .position(|item| ut == item)
+            .map(|i| dates.remove(i));
+    }
+
+    assert_eq!(0, dates.len());
+}
+
+#[cfg(test)]
+fn assert_time(time_results: &TimeResult, required: CalendarTime) {
+    let string_time = match time_results {
+        TimeResult::String(string_time) => string_time,
+        _ => panic!("Was epoch"),
+    };
+
+    assert_eq!(Some(required), string_time.time);
+}
+
+#[cfg(test)]
+fn assert_time_zone(time_results: &TimeResult, required: FixedOffset) {
+    let string_time = match time_results {
+        TimeResult::String(string_time) => string_time,
+        _ => panic!("Was epoch"),
+    };
+
+    let date = NaiveDate::from_ymd(2019, 2, 3);
+    let required_offset = required.offset_from_utc_date(&date).fix();
+    let testing_offset = match string_time.timezone.unwrap() {
+        ParsedZone::Offset(offset) => offset,
+        ParsedZone::Named(_) => *time_results.make_permutations()[0].datetime.offset(),
+    };
+
+    assert_eq!(
+        required_offset.local_minus_utc(),
+        testing_offset.local_minus_utc(),
+        "{:?} != {:?}",
+        required_offset,
+        testing_offset
+    );
+}
+
+#[cfg(test)]
+fn assert_full_output(
+    time_results: &TimeResult,
+    dates: &[CalendarDate],
+    time: CalendarTime,
+    offset: FixedOffset,
+) {
+    assert_contains_date(time_results, dates);
+    assert_time(time_results, time);
+    assert_time_zone(time_results, offset);
+}
+
+#[cfg(test)]
+fn parse_unwrap(input: &str) -> TimeResult {
+    parse(input).unwrap()
+}
+
+#[cfg(test)]
+fn epoch(seconds: i64, nanos: u32, unit: EpochUnit) -> EpochTime {
+    EpochTime {
+        seconds,
+        nanos,
+        source: EpochSource::Unix(unit),
+    }
+}
+
+#[cfg(test)]
+fn first_epoch(input: &str) -> EpochTime {
+    match parse_unwrap(input) {
+        TimeResult::Epoch(mut epochs) => epochs.remove(0),
+        _ => panic!("Was not an epoch"),
+    }
+}
+
+#[test]
+fn parse_epoch_timestamps_samples() {
+    assert_eq!(
+        epoch(1554248133, 0, EpochUnit::Seconds),
+        first_epoch("1554248133")
+    );
+    assert_eq!(
+        epoch(1554248133, 358000000, EpochUnit::Milliseconds),
+        first_epoch("1554248133358")
+    );
+    assert_eq!(
+        epoch(1555438653, 801529000, EpochUnit::Nanoseconds),
+        first_epoch("1555438653801529000")
+    );
+    assert_eq!(
+        epoch(1555438653, 801529000, EpochUnit::Microseconds),
+        first_epoch("1555438653801529")
+    );
+}
+
+#[test]
+fn parse_signed_and_fractional_epochs() {
+    assert_eq!(epoch(-86400, 0, EpochUnit::Seconds), first_epoch("-86400"));
+    assert_eq!(
+        epoch(1543897222, 205_500_000, EpochUnit::Milliseconds),
+        first_epoch("1543897222205.5")
+    );
+    assert_eq!(
+        epoch(9_999_999_999, 999_999_999, EpochUnit::Nanoseconds),
+        first_epoch("9999999999999999999")
+    );
+    assert_eq!(
+        EpochUnit::Nanoseconds,
+        EpochUnit::from_magnitude(parse_whole("18446744073709551615").unwrap())
+    );
+
+    // Too short to be an epoch without `--unit`.
+    for input in &["10.30", "1.5", "-0.5"] {
+        if let Ok(TimeResult::Epoch(_)) = parse(input) {
+            panic!("{} was read as an epoch", input);
+        }
+    }
+    let seconds = ParseOptions {
+        unit: Some(EpochUnit::Seconds),
+        encoding: None,
+        holidays: None,
+    };
+    let base = FixedOffset::east(0).ymd(2019, 6, 1).and_hms(0, 0, 0);
+    assert_eq!(
+        Some(epoch(-1, 500_000_000, EpochUnit::Seconds)),
+        match parse_with_options("-0.5", &base, seconds) {
+            Ok(TimeResult::Epoch(epochs)) => epochs.into_iter().next(),
+            _ => None,
+        }
+    );
+    assert_eq!(
+        epoch(-315_619_201, 500_000_000, EpochUnit::Milliseconds),
+        first_epoch("-315619200500")
+    );
+    assert_eq!(
+        epoch(-1_000_000_001, 999_999_999, EpochUnit::Nanoseconds),
+        first_epoch("-1000000000000000001")
+    );
+    assert_eq!(
+        epoch(1543897222, 205_000_000, EpochUnit::Seconds),
+        first_epoch("1543897222.205")
+    );
+    assert_eq!(
+        epoch(1543897222, 123_456_789, EpochUnit::Seconds),
+        first_epoch("1543897222.1234567891")
+    );
+
+    assert_eq!(
+        chrono::Utc.ymd(1959, 12, 31).and_hms_milli(23, 59, 59, 500),
+        parse_unwrap("-315619200500").to_utc_date_time().unwrap()
+    );
+}
+
+#[test]
+fn epoch_unit_alternatives() {
+    let base = FixedOffset::east(0).ymd(2019, 6, 1).and_hms(0, 0, 0);
+    let unit = |unit: EpochUnit| ParseOptions {
+        unit: Some(unit),
+        encoding: None,
+        holidays: None,
+    };
+
+    // Only one unit lands anywhere near now.
+    assert_eq!(1, parse_unwrap("1543897222").make_permutations().len());
+    assert_eq!(
+        1,
+        parse_unwrap("1543897222205000").make_permutations().len()
+    );
+
+    // Right on the seconds/milliseconds boundary both are possible.
+    let permutations = parse_unwrap("3000000000").make_permutations();
+    assert_eq!(2, permutations.len());
+    assert_eq!("Unix epoch in seconds", permutations[0].interpretation);
+    assert_eq!("Unix epoch in milliseconds", permutations[1].interpretation);
+
+    assert_eq!(
+        TimeResult::Epoch(vec![epoch(1543897, 222_205_000, EpochUnit::Microseconds)]),
+        parse_with_options("1543897222205", &base, unit(EpochUnit::Microseconds)).unwrap()
+    );
+    assert_eq!(
+        TimeResult::Epoch(vec![epoch(
+            1543897222,
+            205_500_000,
+            EpochUnit::Milliseconds
+        )]),
+        parse_with_options("1543897222205.5", &base, unit(EpochUnit::Milliseconds)).unwrap()
+    );
+}
+
+#[test]
+fn parse_encoded_ids() {
+    let base = FixedOffset::east(0).ymd(2019, 6, 1).and_hms(0, 0, 0);
+    let ticks = ParseOptions {
+        unit: None,
+        encoding: Some(Encoding::Ticks),
+        holidays: None,
+    };
+
+    let permutations = parse_unwrap("ea600700-f77b-11e8-8000-000000000000").make_permutations();
+    assert_eq!(1, permutations.len());
+    assert_eq!("UUID timestamp", permutations[0].interpretation);
+    assert_eq!(
+        FixedOffset::east(0).ymd(2018, 12, 4).and_hms(4, 20, 22),
+        permutations[0].datetime
+    );
+
+    let permutations = parse_with_options("636794940220000000", &base, ticks)
+        .unwrap()
+        .make_permutations();
+    assert_eq!(".NET ticks", permutations[0].interpretation);
+    assert_eq!(
+        FixedOffset::east(0).ymd(2018, 12, 4).and_hms(4, 20, 22),
+        permutations[0].datetime
+    );
+
+    assert!(parse_with_options("not ticks", &base, ticks).is_err());
+    assert!(parse("9b2c1a8e-2f4b-4c3e-9d6a-1b2c3d4e5f60").is_err());
+}
+
+#[test]
+fn parse_iso_week_ordinal_and_basic() {
+    for input in &["2018-W49-2", "2018W492", "2018-338", "20181204"] {
+        assert_contains_date(&parse_unwrap(input), &[CalendarDate::new(2018, 12, 4)]);
+    }
+    assert_contains_date(&parse_unwrap("2018-W49"), &[CalendarDate::new(2018, 12, 3)]);
+    assert_contains_date(
+        &parse_unwrap("2020-W53-5"),
+        &[CalendarDate::new(2021, 1, 1)],
+    );
+
+    let zulu = parse_unwrap("20181204T042022Z");
+    assert_contains_date(&zulu, &[CalendarDate::new(2018, 12, 4)]);
+    assert_time(&zulu, CalendarTime::new(4, 20, 22, 0));
+    assert_full_output(
+        &parse_unwrap("20181204T052022.205+0100"),
+        &[CalendarDate::new(2018, 12, 4)],
+        CalendarTime::new(5, 20, 22, 205_000_000),
+        FixedOffset::east(3600),
+    );
+    assert_full_output(
+        &parse_unwrap("2018-W49-2T04:20:22 +0000"),
+        &[CalendarDate::new(2018, 12, 4)],
+        CalendarTime::new(4, 20, 22, 0),
+        FixedOffset::east(0),
+    );
+    assert_full_output(
+        &parse_unwrap("2018-338T04:20:22 +0000"),
+        &[CalendarDate::new(2018, 12, 4)],
+        CalendarTime::new(4, 20, 22, 0),
+        FixedOffset::east(0),
+    );
+
+    assert!(parse("2018-W54-1").is_err());
+    assert!(parse("2018-366").is_err());
+    // Not a date, so still an epoch.
+    match parse_unwrap("20181340") {
+        TimeResult::Epoch(_) => {}
+        _ => panic!("Was not an epoch"),
+    }
+}
+
+#[test]
+fn parse_dates_samples() {
+    assert_contains_date(
+        &parse_unwrap("2019/12/15"),
+        &[CalendarDate::new(2019, 12, 15)],
+    );
+    assert_contains_date(
+        &parse_unwrap("2019-12-15"),
+        &[CalendarDate::new(2019, 12, 15)],
+    );
+    assert_contains_date(
+        &parse_unwrap("2019\\12\\15"),
+        &[CalendarDate::new(2019, 12, 15)],
+    );
+
+    assert_contains_date(
+        &parse_unwrap("2019/15/12"),
+        &[CalendarDate::new(2019, 12, 15)],
+    );
+    assert_contains_date(
+        &parse_unwrap("2019-15-12"),
+        &[CalendarDate::new(2019, 12, 15)],
+    );
+    assert_contains_date(
+        &parse_unwrap("2019\\15\\12"),
+        &[CalendarDate::new(2019, 12, 15)],
+    );
+
+    assert_contains_date(
+        &parse_unwrap("2019/03/04"),
+        &[CalendarDate::new(2019, 4, 3), CalendarDate::new(2019, 3, 4)],
+    );
+    assert_contains_date(
+        &parse_unwrap("2019\\03\\04"),
+        &[CalendarDate::new(2019, 4, 3), CalendarDate::new(2019, 3, 4)],
+    );
+    assert_contains_date(
+        &parse_unwrap("2019-03-04"),
+        &[CalendarDate::new(2019, 4, 3), CalendarDate::new(2019, 3, 4)],
+    );
+
+    assert_contains_date(
+        &parse_unwrap("4-3-19"),
+        &[
+            CalendarDate::new(2019, 4, 3),
+            CalendarDate::new(2019, 3, 4),
+            CalendarDate::new(2004, 3, 19),
+        ],
+    );
+
+    assert_contains_date(&parse_unwrap("4-13-19"), &[CalendarDate::new(2019, 4, 13)]);
+
+    assert_contains_date(
+        &parse_unwrap("13-4-19"),
+        &[
+            CalendarDate::new(2019, 4, 13),
+            CalendarDate::new(2013, 4, 19),
+        ],
+    );
+}
+
+#[test]
+fn parse_named_month_samples() {
+    let base = FixedOffset::east(0).ymd(2019, 6, 1).and_hms(0, 0, 0);
+    let parse_at = |input: &str| parse_with_base(input, &base).unwrap();
+
+    for input in &[
+        "Dec 4 2018",
+        "dec 4, 2018",
+        "4 December 2018",
+        "December 4th, 2018",
+        "4th of Dec 2018",
+        "4-Dec-2018",
+        "4-Dec-18",
+        "2018 Dec 4",
+        "Sept 4 2018",
+        "Tuesday, December 4th, 2018",
+    ] {
+        let expected = if input.starts_with("Sept") {
+            CalendarDate::new(2018, 9, 4)
+        } else {
+            CalendarDate::new(2018, 12, 4)
+        };
+        assert_contains_date(&parse_at(input), &[expected]);
+    }
+
+    assert_full_output(
+        &parse_at("Tue Dec 4 04:20:22 +0000 2018"),
+        &[CalendarDate::new(2018, 12, 4)],
+        CalendarTime::new(4, 20, 22, 0),
+        FixedOffset::east(0),
+    );
+    assert_full_output(
+        &parse_at("4 Dec 04:20 +0000"),
+        &[CalendarDate::new(2018, 12, 4)],
+        CalendarTime::new(4, 20, 0, 0),
+        FixedOffset::east(0),
+    );
+    assert!(parse_with_base("Feb 30 2019", &base).is_err());
+}
+
+#[test]
+fn parse_log_formats() {
+    let base = FixedOffset::east(0).ymd(2019, 6, 1).and_hms(0, 0, 0);
+    let parse_at = |input: &str| parse_with_base(input, &base).unwrap();
+
+    for (input, offset) in &[
+        ("Tue, 04 Dec 2018 04:20:22 +0000", 0),
+        ("Tue, 04 Dec 2018 04:20:22 GMT", 0),
+        ("Tuesday, 04-Dec-18 04:20:22 GMT", 0),
+        ("04/Dec/2018:04:20:22 -0800", -8 * 3600),
+        ("Tue Dec 04 04:20:22 UTC 2018", 0),
+        ("Tue Dec 04 04:20:22 PST 2018", -8 * 3600),
+    ] {
+        assert_full_output(
+            &parse_at(input),
+            &[CalendarDate::new(2018, 12, 4)],
+            CalendarTime::new(4, 20, 22, 0),
+            FixedOffset::east(*offset),
+        );
+    }
+
+    let asctime = parse_at("Tue Dec  4 04:20:22 2018");
+    assert_contains_date(&asctime, &[CalendarDate::new(2018, 12, 4)]);
+    assert_time(&asctime, CalendarTime::new(4, 20, 22, 0));
+
+    // Syslog leaves out the year, so it's the last one that isn't ahead of now.
+    let syslog = parse_at("Dec  4 04:20:22");
+    assert_contains_date(&syslog, &[CalendarDate::new(2018, 12, 4)]);
+    assert_time(&syslog, CalendarTime::new(4, 20, 22, 0));
+    assert_contains_date(
+        &parse_at("May 31 23:59:59"),
+        &[CalendarDate::new(2019, 5, 31)],
+    );
+    assert_contains_date(
+        &parse_at("Jun  2 00:00:00"),
+        &[CalendarDate::new(2019, 6, 2)],
+    );
+    assert_contains_date(
+        &parse_at("Feb 29 12:00:00"),
+        &[CalendarDate::new(2016, 2, 29)],
+    );
+}
+
+#[test]
+fn abbreviations_are_fixed_offsets() {
+    let offset = |input| {
+        let permutations = parse_unwrap(input).make_permutations();
+        assert_eq!(1, permutations.len());
+        assert_eq!(None, permutations[0].zone);
+        permutations[0].datetime.offset().local_minus_utc()
+    };
+
+    // Written out of season, they still mean what they say.
+    assert_eq!(-8 * 3600, offset("4 July 2018 12:00 PST"));
+    assert_eq!(2 * 3600, offset("4 December 2018 10:00 CEST"));
+    assert_eq!(-7 * 3600, offset("4 July 2018 12:00 MST"));
+    assert_eq!(3600, offset("4 July 2018 12:00 BST"));
+
+    let permutations = parse_unwrap("4 July 2018 12:00 PST").make_permutations();
+    assert!(permutations[0]
+        .interpretation
+        .ends_with("reading the ambiguous PST as -08:00"));
+
+    let permutations = parse_unwrap("4 December 2018 09:50 IST").make_permutations();
+    assert_eq!(
+        5 * 3600 + 1800,
+        permutations[0].datetime.offset().local_minus_utc()
+    );
+    assert!(permutations[0]
+        .interpretation
+        .ends_with("reading the ambiguous IST as +05:30"));
+
+    for input in &["Tue, 04 Dec 2018 04:20:22 GMT", "4 July 2018 12:00 MST"] {
+        let permutations = parse_unwrap(input).make_permutations();
+        assert!(!permutations[0].interpretation.contains("ambiguous"));
+    }
+}
+
+#[test]
+fn named_zones_follow_dst() {
+    // The clocks went forward at 02:00, so 03:30 is already summer time.
+    let permutations = parse_unwrap("11 March 2018 03:30 America/New_York").make_permutations();
+    assert_eq!(
+        -4 * 3600,
+        permutations[0].datetime.offset().local_minus_utc()
+    );
+
+    // 01:30 happened twice when the clocks went back.
+    let permutations = parse_unwrap("4 November 2018 01:30 America/New_York").make_permutations();
+    assert_eq!(2, permutations.len());
+    assert_eq!(
+        FixedOffset::east(0).ymd(2018, 11, 4).and_hms(5, 30, 0),
+        permutations[0].datetime
+    );
+    assert_eq!(
+        FixedOffset::east(0).ymd(2018, 11, 4).and_hms(6, 30, 0),
+        permutations[1].datetime
+    );
+
+    // 02:30 never happened when they went forward.
+    assert!(parse("25 March 2018 02:30 Europe/Berlin").is_err());
+
+    // Unless another reading of the date did happen, then both are kept.
+    let permutations = parse_unwrap("2019-03-10 02:30 America/New_York").make_permutations();
+    assert_eq!(2, permutations.len());
+    assert_eq!(
+        FixedOffset::east(0).ymd(2019, 10, 3).and_hms(6, 30, 0),
+        permutations[0].datetime
+    );
+    assert_eq!(
+        FixedOffset::east(0).ymd(2019, 3, 10).and_hms(7, 30, 0),
+        permutations[1].datetime
+    );
+    assert!(permutations[1].interpretation.contains("clocks skip over"));
+
+    // Gaps can be longer than an hour, Samoa skipped a whole day.
+    assert!(parse("2011-12-30 12:00 Pacific/Apia").is_err());
+}
+
+#[test]
+fn preferred_rule_picks_one_permutation() {
+    let permutations = parse_unwrap("04/12/2018 04:20 +0000").make_permutations();
+    assert!(permutations.len() > 1);
+
+    let chosen = choose_permutation(permutations.clone(), DateRule::DayMonthYear);
+    assert_eq!(1, chosen.len());
+    assert_eq!(
+        FixedOffset::east(0).ymd(2018, 12, 4).and_hms(4, 20, 0),
+        chosen[0].datetime
+    );
+
+    let chosen = choose_permutation(permutations, DateRule::MonthDayYear);
+    assert_eq!(
+        FixedOffset::east(0).ymd(2018, 4, 12).and_hms(4, 20, 0),
+        chosen[0].datetime
+    );
+
+    let chosen = choose_permutation(
+        parse_unwrap("1543897222").make_permutations(),
+        DateRule::MonthDayYear,
+    );
+    assert_eq!(1, chosen.len());
+    assert_eq!(None, chosen[0].rule);
+}
+
+#[test]
+fn locale_date_order() {
+    assert_eq!(DateRule::MonthDayYear, DateRule::from_locale("en_US.UTF-8"));
+    assert_eq!(DateRule::DayMonthYear, DateRule::from_locale("en_GB.UTF-8"));
+    assert_eq!(DateRule::DayMonthYear, DateRule::from_locale("de_DE@euro"));
+    assert_eq!(DateRule::YearMonthDay, DateRule::from_locale("ja_JP.UTF-8"));
+    assert_eq!(DateRule::YearMonthDay, DateRule::from_locale("C"));
+}
+
+#[test]
+fn weekday_narrows_dates() {
+    assert_contains_date(
+        &parse_unwrap("Tue 12/4/18"),
+        &[CalendarDate::new(2018, 12, 4)],
+    );
+    assert_contains_date(
+        &parse_unwrap("Thursday 12/4/18"),
+        &[CalendarDate::new(2018, 4, 12)],
+    );
+    assert!(parse("Mon Dec 4 2018").is_err());
+}
+
+#[test]
+fn parse_times_samples() {
+    assert_time(&parse_unwrap("10:30"), CalendarTime::new(10, 30, 0, 0));
+    assert_time(&parse_unwrap("10:30:45"), CalendarTime::new(10, 30, 45, 0));
+    assert_time(
+        &parse_unwrap("10:30:45.123"),
+        CalendarTime::new(10, 30, 45, 123_000_000),
+    );
+    assert_time(
+        &parse_unwrap("10:30:45.123456"),
+        CalendarTime::new(10, 30, 45, 123_456_000),
+    );
+    assert_time(
+        &parse_unwrap("10:30:45.123456789"),
+        CalendarTime::new(10, 30, 45, 123_456_789),
+    );
+    assert_time(
+        &parse_unwrap("10:30:45.123456789 am"),
+        CalendarTime::new(10, 30, 45, 123_456_789),
+    );
+    assert_time(
+        &parse_unwrap("10:30:45.123456789 AM"),
+        CalendarTime::new(10, 30, 45, 123_456_789),
+    );
+    assert_time(
+        &parse_unwrap("10:30:45.123456789 pm"),
+        CalendarTime::new(22, 30, 45, 123_456_789),
+    );
+    assert_time(
+        &parse_unwrap("10:30:45.123456789 PM"),
+        CalendarTime::new(22, 30, 45, 123_456_789),
+    );
+    assert_time(&parse_unwrap("12:30 pm"), CalendarTime::new(12, 30, 0, 0));
+    assert_time(&parse_unwrap("12:05 am"), CalendarTime::new(0, 5, 0, 0));
+    assert_eq!(1, parse_unwrap("12:30 pm").make_permutations().len());
+
+    assert_time(&parse_unwrap("9am"), CalendarTime::new(9, 0, 0, 0));
+    assert_time(&parse_unwrap("4 PM"), CalendarTime::new(16, 0, 0, 0));
+    assert_time(&parse_unwrap("12am"), CalendarTime::new(0, 0, 0, 0));
+    assert_time(
+        &parse_unwrap("2018-12-04 9pm PST"),
+        CalendarTime::new(21, 0, 0, 0),
+    );
+    assert!(parse("13pm").is_err());
+}
+
+#[test]
+fn test_timezone() {
+    assert!(TIME_ZONE.is_match("+12"));
+    assert!(TIME_ZONE.is_match("+1200"));
+    assert!(TIME_ZONE.is_match("+12:00"));
+    assert!(TIME_ZONE.is_match("-12:00"));
+
+    let twelve_hours = 12 * 60 * 60;
+    let ten_hours = 10 * 60 * 60;
+
+    assert_time_zone(&parse_unwrap("10:30+1200"), FixedOffset::east(twelve_hours));
+    assert_time_zone(
+        &parse_unwrap("10:30+12:00"),
+        FixedOffset::east(twelve_hours),
+    );
+    assert_time_zone(
+        &parse_unwrap("10:30-12:00"),
+        FixedOffset::west(twelve_hours),
+    );
+    assert_time_zone(&parse_unwrap("10:30+12"), FixedOffset::east(twelve_hours));
+    assert_time_zone(&parse_unwrap("10:30+0000"), FixedOffset::west(0));
+    assert_time_zone(&parse_unwrap("10:30-10"), FixedOffset::west(ten_hours));
+}
+
+#[test]
+fn test_real_examples() {
+    assert_full_output(
+        &parse_unwrap("2018-12-04T04:20:22.205838800+00:00"),
+        &[CalendarDate::new(2018, 12, 4)],
+        CalendarTime::new(4, 20, 22, 205_838_800),
+        FixedOffset::east(0),
+    );
+    assert_full_output(
+        &parse_unwrap("2019-02-08T08:00:00+0000"),
+        &[CalendarDate::new(2019, 2, 8)],
+        CalendarTime::new(8, 0, 0, 0),
+        FixedOffset::east(0),
+    );
+    assert_full_output(
+        &parse_unwrap("2019-02-08T08:00:00 PST"),
+        &[CalendarDate::new(2019, 2, 8)],
+        CalendarTime::new(8, 0, 0, 0),
+        FixedOffset::west(Duration::hours(8).num_seconds() as i32),
+    );
+    assert_full_output(
+        &parse_unwrap("2019-02-08T08:00:00 America/Los_Angeles"),
+        &[CalendarDate::new(2019, 2, 8)],
+        CalendarTime::new(8, 0, 0, 0),
+        FixedOffset::west(Duration::hours(8).num_seconds() as i32),
+    );
+    assert_full_output(
+        &parse_unwrap("2019-02-08T08:00:00 [America/Los_Angeles]"),
+        &[CalendarDate::new(2019, 2, 8)],
+        CalendarTime::new(8, 0, 0, 0),
+        FixedOffset::west(Duration::hours(8).num_seconds() as i32),
+    );
+}
+
+#[test]
+fn parse_relative_samples() {
+    let base = FixedOffset::east(0).ymd(2018, 12, 4).and_hms(4, 20, 22);
+
+    assert_full_output(
+        &parse_with_base("yesterday 17:00 PST", &base).unwrap(),
+        &[CalendarDate::new(2018, 12, 2)],
+        CalendarTime::new(17, 0, 0, 0),
+        FixedOffset::west(Duration::hours(8).num_seconds() as i32),
+    );
+    assert_full_output(
+        &parse_with_base("now - 2h", &base).unwrap(),
+        &[CalendarDate::new(2018, 12, 4)],
+        CalendarTime::new(2, 20, 22, 0),
+        FixedOffset::east(0),
+    );
+    assert_full_output(
+        &parse_with_base("now", &base).unwrap(),
+        &[CalendarDate::new(2018, 12, 4)],
+        CalendarTime::new(4, 20, 22, 0),
+        FixedOffset::east(0),
+    );
+    assert_full_output(
+        &parse_with_base("next tuesday 9:30 +05:30", &base).unwrap(),
+        &[CalendarDate::new(2018, 12, 11)],
+        CalendarTime::new(9, 30, 0, 0),
+        FixedOffset::east(Duration::minutes(330).num_seconds() as i32),
+    );
+    assert!(parse_with_base("2018-12-04 3 days ago", &base).is_err());
+}
+
+#[test]
+fn errors_name_the_part_that_failed() {
+    let failure = |input: &str| {
+        let error = parse(input).unwrap_err();
+        (error.kind(), s!(error.fragment()))
+    };
+
+    assert_eq!(
+        (ParseErrorKind::InvalidDate, s!("2018-W54-1")),
+        failure("2018-W54-1")
+    );
+    assert_eq!(
+        (ParseErrorKind::InvalidDate, s!("Feb 30 2019")),
+        failure("Feb 30 2019 04:20")
+    );
+    assert_eq!(
+        (ParseErrorKind::WrongWeekday, s!("Mon")),
+        failure("Mon Dec 4 2018")
+    );
+    assert_eq!(
+        (ParseErrorKind::SkippedTime, s!("02:30")),
+        failure("25 March 2018 02:30 Europe/Berlin")
+    );
+    assert_eq!(
+        (
+            ParseErrorKind::RelativeWithDate,
+            s!("2018-12-04 3 days ago")
+        ),
+        failure("2018-12-04 3 days ago")
+    );
+    assert_eq!(
+        (ParseErrorKind::OutOfRange, s!("99999999999999999999.5")),
+        failure("99999999999999999999.5")
+    );
+    assert_eq!((ParseErrorKind::InvalidTime, s!("25:00")), failure("25:00"));
+    assert_eq!(
+        (ParseErrorKind::InvalidTime, s!("13:30 pm")),
+        failure("13:30 pm")
+    );
+    assert_eq!(
+        (ParseErrorKind::UnknownFormat, s!("flux capacitor")),
+        failure("flux capacitor")
+    );
+    assert_eq!(
+        "Invalid week date 2018-W54-1",
+        parse("2018-W54-1").unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn find_times_in_log_lines() {
+    let base = FixedOffset::east(0).ymd(2018, 12, 4).and_hms(0, 0, 0);
+    let found = |text: &str| -> Vec<String> {
+        find_times(text, &base, ParseOptions::default())
+            .into_iter()
+            .map(|(range, _)| text[range].to_string())
+            .collect()
+    };
+
+    assert_eq!(
+        vec!["2018-12-04T04:20:22Z"],
+        found("INFO 2018-12-04T04:20:22Z started worker 3")
+    );
+    assert_eq!(
+        vec!["Tue, 4 Dec 2018 04:20:22 +0000"],
+        found("Date: Tue, 4 Dec 2018 04:20:22 +0000")
+    );
+    assert_eq!(
+        vec!["Tue Dec 04 04:20:22 PST 2018"],
+        found("at Tue Dec 04 04:20:22 PST 2018 in main")
+    );
+    assert_eq!(
+        vec!["1543897222", "1543897222123"],
+        found("took 1543897222 then 1543897222123 ms")
+    );
+    assert_eq!(
+        vec!["2018-12-04", "04:20 PST"],
+        found("from 2018-12-04 until 04:20 PST")
+    );
+    assert_eq!(
+        vec!["04/Dec/2018:04:20:22 +0000", "04:20 America/New_York"],
+        found("[04/Dec/2018:04:20:22 +0000] moved to 04:20 America/New_York")
+    );
+    assert!(found("see 1/2/3 or may 5, line 42 of app2018-12-04.log").is_empty());
+    assert_eq!(vec!["12:30 pm PST"], found("lunch at 12:30 pm PST today"));
+    assert!(found("elapsed 45:99 UTC").is_empty());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs::File;
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parsing_inputs() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/time_known_formats.txt");
+
+        let f = File::open(d).unwrap();
+        let file = BufReader::new(&f);
+        for line in file.lines() {
+            let l = line.unwrap();
+            println!("Validating {} as input.", l);
+            if let Err(s) = parse(&l) {
+                panic!("Unable to parse {}", s)
+            }
+        }
+    }
+}
@@ -0,0 +1,803 @@
+use chrono::naive::{NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::offset::LocalResult;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Offset, TimeZone, Weekday};
+use regex::Regex;
+
+use super::calendar::Calendar;
+
+lazy_static! {
+    static ref DURATION_PART: Regex = Regex::new(r"(?P<amount>\d+)(?P<unit>[a-z]+)").unwrap();
+    static ref CLOCK_HOUR: Regex = Regex::new(r"^(?P<hour>\d{1,2})(?P<format>am|pm)$").unwrap();
+    static ref ISO_DURATION: Regex = Regex::new(r"(?i)^(?P<sign>[+-])?P(?:(?P<years>\d+)Y)?(?:(?P<months>\d+)M)?(?:(?P<weeks>\d+)W)?(?:(?P<days>\d+)D)?(?:T(?:(?P<hours>\d+)H)?(?:(?P<minutes>\d+)M)?(?:(?P<seconds>\d+)(?:[\.,](?P<fraction>\d{1,9}))?S)?)?$").unwrap();
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TimeUnit {
+    Nanosecond,
+    Millisecond,
+    Second,
+    Minute,
+    Hour,
+    Day,
+    /// A weekday that isn't a holiday.
+    BusinessDay,
+    Week,
+    Month,
+    Year,
+}
+
+impl TimeUnit {
+    fn parse(input: &str) -> Option<Self> {
+        let unit = match input {
+            "ns" | "nano" | "nanos" | "nanosecond" | "nanoseconds" => TimeUnit::Nanosecond,
+            "ms" | "milli" | "millis" | "millisecond" | "milliseconds" => TimeUnit::Millisecond,
+            "s" | "sec" | "secs" | "second" | "seconds" => TimeUnit::Second,
+            "m" | "min" | "mins" | "minute" | "minutes" => TimeUnit::Minute,
+            "h" | "hr" | "hrs" | "hour" | "hours" => TimeUnit::Hour,
+            "d" | "day" | "days" => TimeUnit::Day,
+            "bd" | "workday" | "workdays" => TimeUnit::BusinessDay,
+            "w" | "wk" | "wks" | "week" | "weeks" => TimeUnit::Week,
+            "mo" | "mon" | "month" | "months" => TimeUnit::Month,
+            "y" | "yr" | "yrs" | "year" | "years" => TimeUnit::Year,
+            _ => return None,
+        };
+
+        Some(unit)
+    }
+}
+
+/// A signed amount of a single unit, e.g. `-3 days`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Shift {
+    pub amount: i64,
+    pub unit: TimeUnit,
+}
+
+impl Shift {
+    pub fn new(amount: i64, unit: TimeUnit) -> Self {
+        Shift { amount, unit }
+    }
+
+    pub fn negate(&self) -> Self {
+        Shift::new(-self.amount, self.unit)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum DayAnchor {
+    Now,
+    Today,
+    Yesterday,
+    Tomorrow,
+    Next(Weekday),
+    Last(Weekday),
+    This(Weekday),
+}
+
+/// An expression like `yesterday 17:00` or `now - 2h` that only has meaning
+/// once it's resolved against a base time.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RelativeTime {
+    pub anchor: DayAnchor,
+    pub time_of_day: Option<NaiveTime>,
+    pub shifts: Vec<Shift>,
+}
+
+impl RelativeTime {
+    /// Resolve the expression in `zone`. A `time_of_day` found elsewhere in the
+    /// input wins over one the relative expression carried (`9am`, `noon`).
+    pub fn resolve<Tz: TimeZone>(
+        &self,
+        base: &DateTime<FixedOffset>,
+        time_of_day: Option<NaiveTime>,
+        zone: &Tz,
+        holidays: &Calendar,
+    ) -> Option<DateTime<Tz>> {
+        let local = base.with_timezone(zone);
+        let today = local.date().naive_local();
+
+        let date = match self.anchor {
+            DayAnchor::Now | DayAnchor::Today => today,
+            DayAnchor::Yesterday => today.pred(),
+            DayAnchor::Tomorrow => today.succ(),
+            DayAnchor::Next(weekday) => today + Duration::days(days_until(today, weekday, false)),
+            DayAnchor::Last(weekday) => today - Duration::days(days_since(today, weekday)),
+            DayAnchor::This(weekday) => today + Duration::days(days_until(today, weekday, true)),
+        };
+
+        let time = match (time_of_day.or(self.time_of_day), &self.anchor) {
+            (Some(time), _) => time,
+            (None, DayAnchor::Next(_))
+            | (None, DayAnchor::Last(_))
+            | (None, DayAnchor::This(_)) => NaiveTime::from_hms(0, 0, 0),
+            (None, _) => local.time(),
+        };
+
+        let start = from_local(zone, &NaiveDateTime::new(date, time))?;
+
+        apply_shifts(start, &self.shifts, holidays)
+    }
+}
+
+/// Add every shift to `start`. Days and larger are calendar units, applied to
+/// the wall clock in `start`'s zone; months and years clamp to the length of
+/// the target month, and business days skip weekends and `holidays`. Hours
+/// and smaller are exact elapsed time.
+pub fn apply_shifts<Tz: TimeZone>(
+    start: DateTime<Tz>,
+    shifts: &[Shift],
+    holidays: &Calendar,
+) -> Option<DateTime<Tz>> {
+    let mut current = start;
+
+    for shift in shifts {
+        current = match shift.unit {
+            TimeUnit::Nanosecond => add_elapsed(&current, Duration::nanoseconds(shift.amount))?,
+            TimeUnit::Millisecond => add_elapsed(&current, Duration::milliseconds(shift.amount))?,
+            TimeUnit::Second => add_elapsed(&current, Duration::seconds(shift.amount))?,
+            TimeUnit::Minute => add_elapsed(&current, Duration::minutes(shift.amount))?,
+            TimeUnit::Hour => add_elapsed(&current, Duration::hours(shift.amount))?,
+            TimeUnit::Day => shift_local(&current, |x| {
+                x.checked_add_signed(Duration::days(shift.amount))
+            })?,
+            TimeUnit::BusinessDay => shift_local(&current, |x| {
+                holidays
+                    .add_business_days(x.date(), shift.amount)
+                    .map(|date| date.and_time(x.time()))
+            })?,
+            TimeUnit::Week => shift_local(&current, |x| {
+                x.checked_add_signed(Duration::weeks(shift.amount))
+            })?,
+            TimeUnit::Month => shift_local(&current, |x| {
+                add_months(x.date(), shift.amount).map(|date| date.and_time(x.time()))
+            })?,
+            TimeUnit::Year => shift_local(&current, |x| {
+                add_months(x.date(), shift.amount * 12).map(|date| date.and_time(x.time()))
+            })?,
+        };
+    }
+
+    Some(current)
+}
+
+/// Adding a duration keeps the offset the time started with, so look it up
+/// again in case a clock change was crossed.
+pub fn add_elapsed<Tz: TimeZone>(
+    current: &DateTime<Tz>,
+    duration: Duration,
+) -> Option<DateTime<Tz>> {
+    let shifted = current.clone().checked_add_signed(duration)?;
+    Some(shifted.with_timezone(&current.timezone()))
+}
+
+fn shift_local<Tz: TimeZone, F>(current: &DateTime<Tz>, op: F) -> Option<DateTime<Tz>>
+where
+    F: Fn(NaiveDateTime) -> Option<NaiveDateTime>,
+{
+    let shifted = op(current.naive_local())?;
+    from_local(&current.timezone(), &shifted)
+}
+
+/// Map a wall clock time into `tz`. Ambiguous times take the earlier instant,
+/// and times that fall in a gap are pushed forward by the length of the gap.
+pub fn from_local<Tz: TimeZone>(tz: &Tz, local: &NaiveDateTime) -> Option<DateTime<Tz>> {
+    match tz.from_local_datetime(local) {
+        LocalResult::Single(value) => Some(value),
+        LocalResult::Ambiguous(earliest, _) => Some(earliest),
+        LocalResult::None => {
+            // Read it in the offset from before the gap, which lands as far
+            // past the gap as it was into it. Gaps can be a whole day long.
+            let before = tz
+                .offset_from_utc_datetime(&local.checked_sub_signed(Duration::days(2))?)
+                .fix();
+            let utc =
+                local.checked_sub_signed(Duration::seconds(i64::from(before.local_minus_utc())))?;
+            Some(tz.from_utc_datetime(&utc))
+        }
+    }
+}
+
+pub fn add_months(date: NaiveDate, months: i64) -> Option<NaiveDate> {
+    let total = i64::from(date.year()) * 12 + i64::from(date.month0()) + months;
+    let year = total.div_euclid(12) as i32;
+    let month = total.rem_euclid(12) as u32 + 1;
+    let day = date.day().min(days_in_month(year, month)?);
+
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+pub fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+
+    NaiveDate::from_ymd_opt(next_year, next_month, 1).map(|x| x.pred().day())
+}
+
+fn days_until(from: NaiveDate, weekday: Weekday, include_today: bool) -> i64 {
+    let days = (7 + weekday.num_days_from_monday() - from.weekday().num_days_from_monday()) % 7;
+    if days == 0 && !include_today {
+        7
+    } else {
+        i64::from(days)
+    }
+}
+
+fn days_since(from: NaiveDate, weekday: Weekday) -> i64 {
+    let days = (7 + from.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+    if days == 0 {
+        7
+    } else {
+        i64::from(days)
+    }
+}
+
+pub fn parse_weekday(input: &str) -> Option<Weekday> {
+    let weekday = match input {
+        "mon" | "monday" => Weekday::Mon,
+        "tue" | "tues" | "tuesday" => Weekday::Tue,
+        "wed" | "wednesday" => Weekday::Wed,
+        "thu" | "thur" | "thurs" | "thursday" => Weekday::Thu,
+        "fri" | "friday" => Weekday::Fri,
+        "sat" | "saturday" => Weekday::Sat,
+        "sun" | "sunday" => Weekday::Sun,
+        _ => return None,
+    };
+
+    Some(weekday)
+}
+
+fn parse_clock_hour(input: &str) -> Option<NaiveTime> {
+    match input {
+        "noon" => return Some(NaiveTime::from_hms(12, 0, 0)),
+        "midnight" => return Some(NaiveTime::from_hms(0, 0, 0)),
+        _ => {}
+    }
+
+    let captures = CLOCK_HOUR.captures(input)?;
+    let hour = captures
+        .name("hour")
+        .unwrap()
+        .as_str()
+        .parse::<u32>()
+        .ok()?;
+    if hour == 0 || hour > 12 {
+        return None;
+    }
+
+    let hour = match (captures.name("format").unwrap().as_str(), hour) {
+        ("am", 12) => 0,
+        ("am", hour) => hour,
+        ("pm", 12) => 12,
+        (_, hour) => hour + 12,
+    };
+
+    NaiveTime::from_hms_opt(hour, 0, 0)
+}
+
+/// Parse a single token like `90m` or `3d4h` into shifts.
+fn parse_compound(input: &str) -> Option<Vec<Shift>> {
+    let mut shifts = Vec::new();
+    let mut covered = 0;
+
+    for captures in DURATION_PART.captures_iter(input) {
+        let whole = captures.get(0).unwrap();
+        if whole.start() != covered {
+            return None;
+        }
+        covered = whole.end();
+
+        let amount = captures
+            .name("amount")
+            .unwrap()
+            .as_str()
+            .parse::<i64>()
+            .ok()?;
+        let unit = TimeUnit::parse(captures.name("unit").unwrap().as_str())?;
+        shifts.push(Shift::new(amount, unit));
+    }
+
+    if shifts.is_empty() || covered != input.len() {
+        None
+    } else {
+        Some(shifts)
+    }
+}
+
+/// Parse a run of duration words starting at `start`, such as `3 days`,
+/// `1h 30m` or `2 weeks and 1 day`. Returns the shifts and how many words
+/// were used.
+pub fn parse_durations(words: &[String], start: usize) -> Option<(Vec<Shift>, usize)> {
+    let mut shifts = Vec::new();
+    let mut index = start;
+
+    while index < words.len() {
+        let word = words[index].as_str();
+
+        if word == "and" || word == "," {
+            if shifts.is_empty() {
+                break;
+            }
+            index += 1;
+            continue;
+        }
+
+        let word = word.trim_end_matches(',');
+
+        if let Ok(amount) = word.parse::<i64>() {
+            // `business days` is the one unit written as two words.
+            if words.get(index + 1).map(|x| x.as_str()) == Some("business")
+                && words
+                    .get(index + 2)
+                    .and_then(|x| TimeUnit::parse(x.trim_end_matches(',')))
+                    == Some(TimeUnit::Day)
+            {
+                shifts.push(Shift::new(amount, TimeUnit::BusinessDay));
+                index += 3;
+                continue;
+            }
+
+            match words
+                .get(index + 1)
+                .and_then(|x| TimeUnit::parse(x.trim_end_matches(',')))
+            {
+                Some(unit) => {
+                    shifts.push(Shift::new(amount, unit));
+                    index += 2;
+                    continue;
+                }
+                None => break,
+            }
+        }
+
+        match parse_compound(word) {
+            Some(mut parsed) => {
+                shifts.append(&mut parsed);
+                index += 1;
+            }
+            None => break,
+        }
+    }
+
+    // a trailing `and` belongs to whatever follows
+    while index > start && words[index - 1] == "and" {
+        index -= 1;
+    }
+
+    if shifts.is_empty() {
+        None
+    } else {
+        Some((shifts, index - start))
+    }
+}
+
+/// Parse a signed duration like `-90m`, `+ 3d4h` or `- 2 hours`.
+fn parse_signed_durations(words: &[String], start: usize) -> Option<(Vec<Shift>, usize)> {
+    let word = words.get(start)?;
+
+    let (negative, rest) = if let Some(rest) = word.strip_prefix('-') {
+        (true, rest)
+    } else if let Some(rest) = word.strip_prefix('+') {
+        (false, rest)
+    } else {
+        return None;
+    };
+
+    let (shifts, used) = if rest.is_empty() {
+        let (shifts, used) = parse_durations(words, start + 1)?;
+        (shifts, used + 1)
+    } else {
+        let mut patched = words.to_vec();
+        patched[start] = rest.to_string();
+        parse_durations(&patched, start)?
+    };
+
+    if negative {
+        Some((shifts.iter().map(|x| x.negate()).collect(), used))
+    } else {
+        Some((shifts, used))
+    }
+}
+
+/// Parse an ISO 8601 duration like `P1DT2H` or `-PT90M`.
+fn parse_iso_duration(input: &str) -> Option<Vec<Shift>> {
+    let captures = ISO_DURATION.captures(input)?;
+    let sign = match captures.name("sign").map(|x| x.as_str()) {
+        Some("-") => -1,
+        _ => 1,
+    };
+
+    let mut shifts = Vec::new();
+    for (name, unit) in &[
+        ("years", TimeUnit::Year),
+        ("months", TimeUnit::Month),
+        ("weeks", TimeUnit::Week),
+        ("days", TimeUnit::Day),
+        ("hours", TimeUnit::Hour),
+        ("minutes", TimeUnit::Minute),
+        ("seconds", TimeUnit::Second),
+    ] {
+        if let Some(value) = captures.name(name) {
+            let amount = value.as_str().parse::<i64>().ok()?;
+            shifts.push(Shift::new(sign * amount, *unit));
+        }
+    }
+
+    if let Some(fraction) = captures.name("fraction") {
+        let digits = fraction.as_str();
+        let nanos = digits.parse::<i64>().ok()? * 10i64.pow(9 - digits.len() as u32);
+        shifts.push(Shift::new(sign * nanos, TimeUnit::Nanosecond));
+    }
+
+    if shifts.is_empty() {
+        None
+    } else {
+        Some(shifts)
+    }
+}
+
+/// Parse standalone durations such as `+3d4h`, `P1DT2H`, `-90m` or
+/// `2 weeks`. Durations without a sign are positive, and a run of them like
+/// `+1d -2h` is summed.
+pub fn parse_shifts(input: &str) -> Option<Vec<Shift>> {
+    let words: Vec<String> = input.split_whitespace().map(|x| x.to_lowercase()).collect();
+
+    let mut shifts = Vec::new();
+    let mut index = 0;
+    while index < words.len() {
+        if let Some(mut parsed) = parse_iso_duration(&words[index]) {
+            shifts.append(&mut parsed);
+            index += 1;
+            continue;
+        }
+
+        let (mut parsed, used) =
+            parse_signed_durations(&words, index).or_else(|| parse_durations(&words, index))?;
+        shifts.append(&mut parsed);
+        index += used;
+    }
+
+    if shifts.is_empty() {
+        None
+    } else {
+        Some(shifts)
+    }
+}
+
+/// Look for a relative expression in `input`. When one is found, returns it
+/// along with whatever words it did not use so the absolute parsers can still
+/// pick out clock times and zones.
+pub fn extract(input: &str) -> Option<(RelativeTime, String)> {
+    let original: Vec<&str> = input.split_whitespace().collect();
+    let words: Vec<String> = original.iter().map(|x| x.to_lowercase()).collect();
+
+    let mut used = vec![false; words.len()];
+    let mut anchor = None;
+    let mut time_of_day = None;
+    let mut shifts = Vec::new();
+    let mut found = false;
+
+    let mut index = 0;
+    while index < words.len() {
+        let word = words[index].as_str();
+        let mut consumed = 0;
+
+        match word {
+            "now" => {
+                anchor = Some(DayAnchor::Now);
+                consumed = 1;
+            }
+            "today" => {
+                anchor = Some(DayAnchor::Today);
+                consumed = 1;
+            }
+            "yesterday" => {
+                anchor = Some(DayAnchor::Yesterday);
+                consumed = 1;
+            }
+            "tomorrow" => {
+                anchor = Some(DayAnchor::Tomorrow);
+                consumed = 1;
+            }
+            "next" | "last" | "this" if index + 1 < words.len() => {
+                let target = words[index + 1].as_str();
+                if let Some(weekday) = parse_weekday(target) {
+                    anchor = Some(match word {
+                        "next" => DayAnchor::Next(weekday),
+                        "last" => DayAnchor::Last(weekday),
+                        _ => DayAnchor::This(weekday),
+                    });
+                    consumed = 2;
+                } else if let Some(unit) = TimeUnit::parse(target) {
+                    match word {
+                        "next" => shifts.push(Shift::new(1, unit)),
+                        "last" => shifts.push(Shift::new(-1, unit)),
+                        _ => {}
+                    }
+                    consumed = 2;
+                }
+            }
+            "in" => {
+                if let Some((mut parsed, count)) = parse_durations(&words, index + 1) {
+                    shifts.append(&mut parsed);
+                    consumed = count + 1;
+                }
+            }
+            _ => {
+                if let Some(time) = parse_clock_hour(word) {
+                    time_of_day = Some(time);
+                    used[index] = true;
+                    index += 1;
+                    continue;
+                }
+
+                if let Some((mut parsed, count)) = parse_signed_durations(&words, index) {
+                    shifts.append(&mut parsed);
+                    consumed = count;
+                } else if let Some((parsed, count)) = parse_durations(&words, index) {
+                    let next = words.get(index + count).map(|x| x.as_str());
+                    let after = words.get(index + count + 1).map(|x| x.as_str());
+                    match (next, after) {
+                        (Some("ago"), _) => {
+                            shifts.extend(parsed.iter().map(|x| x.negate()));
+                            consumed = count + 1;
+                        }
+                        (Some("from"), Some("now")) => {
+                            shifts.extend(parsed);
+                            consumed = count + 2;
+                        }
+                        (Some("later"), _) | (Some("hence"), _) => {
+                            shifts.extend(parsed);
+                            consumed = count + 1;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        if consumed == 0 {
+            index += 1;
+        } else {
+            found = true;
+            for flag in used.iter_mut().skip(index).take(consumed) {
+                *flag = true;
+            }
+            index += consumed;
+        }
+    }
+
+    if !found {
+        return None;
+    }
+
+    let remaining: Vec<&str> = original
+        .iter()
+        .zip(used.iter())
+        .filter(|(_, used)| !**used)
+        .map(|(word, _)| *word)
+        .collect();
+
+    let relative = RelativeTime {
+        anchor: anchor.unwrap_or(DayAnchor::Now),
+        time_of_day,
+        shifts,
+    };
+
+    Some((relative, remaining.join(" ")))
+}
+
+#[cfg(test)]
+fn base_time() -> DateTime<FixedOffset> {
+    // Tuesday
+    FixedOffset::west(8 * 3600)
+        .ymd(2018, 12, 4)
+        .and_hms(10, 15, 30)
+}
+
+#[cfg(test)]
+fn resolve(input: &str) -> DateTime<FixedOffset> {
+    let (relative, _) = extract(input).unwrap();
+    relative
+        .resolve(
+            &base_time(),
+            None,
+            base_time().offset(),
+            &Calendar::default(),
+        )
+        .unwrap()
+}
+
+#[test]
+fn parse_relative_offsets() {
+    let base = base_time();
+
+    assert_eq!(base - Duration::days(3), resolve("3 days ago"));
+    assert_eq!(base + Duration::minutes(90), resolve("in 90 minutes"));
+    assert_eq!(base - Duration::hours(2), resolve("now - 2h"));
+    assert_eq!(base - Duration::hours(2), resolve("now -2h"));
+    assert_eq!(base + Duration::days(3), resolve("in 3 business days"));
+    assert_eq!(base - Duration::days(7), resolve("5 business days ago"));
+    assert_eq!(
+        base + Duration::days(3) + Duration::hours(4),
+        resolve("+3d4h")
+    );
+    assert_eq!(
+        base - Duration::weeks(2) - Duration::days(1),
+        resolve("2 weeks and 1 day ago")
+    );
+    assert_eq!(base + Duration::hours(1), resolve("1 hour from now"));
+}
+
+#[test]
+fn parse_relative_days() {
+    let zone = FixedOffset::west(8 * 3600);
+
+    assert_eq!(
+        zone.ymd(2018, 12, 3).and_hms(10, 15, 30),
+        resolve("yesterday")
+    );
+    assert_eq!(
+        zone.ymd(2018, 12, 5).and_hms(10, 15, 30),
+        resolve("tomorrow")
+    );
+    assert_eq!(
+        zone.ymd(2018, 12, 11).and_hms(9, 0, 0),
+        resolve("next tuesday 9am")
+    );
+    assert_eq!(
+        zone.ymd(2018, 12, 4).and_hms(0, 0, 0),
+        resolve("this tuesday")
+    );
+    assert_eq!(
+        zone.ymd(2018, 11, 30).and_hms(12, 0, 0),
+        resolve("last fri noon")
+    );
+    assert_eq!(
+        zone.ymd(2019, 1, 4).and_hms(10, 15, 30),
+        resolve("next month")
+    );
+}
+
+#[test]
+fn leaves_absolute_parts_alone() {
+    let (relative, remaining) = extract("yesterday 17:00 PST").unwrap();
+
+    assert_eq!(DayAnchor::Yesterday, relative.anchor);
+    assert_eq!("17:00 PST", remaining);
+
+    assert_eq!(None, extract("2018-12-04 04:20:22 +0000"));
+    assert_eq!(None, extract("12/4/18 04:20:22 am"));
+    assert_eq!(None, extract("1543897222"));
+}
+
+#[test]
+fn parse_standalone_durations() {
+    assert_eq!(
+        Some(vec![
+            Shift::new(3, TimeUnit::Day),
+            Shift::new(4, TimeUnit::Hour)
+        ]),
+        parse_shifts("+3d4h")
+    );
+    assert_eq!(
+        Some(vec![
+            Shift::new(1, TimeUnit::Day),
+            Shift::new(2, TimeUnit::Hour)
+        ]),
+        parse_shifts("P1DT2H")
+    );
+    assert_eq!(
+        Some(vec![
+            Shift::new(-1, TimeUnit::Month),
+            Shift::new(-5, TimeUnit::Second),
+            Shift::new(-250_000_000, TimeUnit::Nanosecond)
+        ]),
+        parse_shifts("-P1MT5.25S")
+    );
+    assert_eq!(
+        Some(vec![
+            Shift::new(0, TimeUnit::Second),
+            Shift::new(100, TimeUnit::Nanosecond)
+        ]),
+        parse_shifts("PT0.0000001S")
+    );
+    assert_eq!(
+        Some(vec![Shift::new(-90, TimeUnit::Minute)]),
+        parse_shifts("-90m")
+    );
+    assert_eq!(
+        Some(vec![Shift::new(2, TimeUnit::Week)]),
+        parse_shifts("2 weeks")
+    );
+    assert_eq!(
+        Some(vec![
+            Shift::new(1, TimeUnit::Day),
+            Shift::new(-2, TimeUnit::Hour)
+        ]),
+        parse_shifts("+1d -2h")
+    );
+    assert_eq!(
+        Some(vec![Shift::new(5, TimeUnit::BusinessDay)]),
+        parse_shifts("+5 business days")
+    );
+    assert_eq!(
+        Some(vec![
+            Shift::new(-1, TimeUnit::BusinessDay),
+            Shift::new(2, TimeUnit::Hour)
+        ]),
+        parse_shifts("-1bd +2h")
+    );
+    assert_eq!(None, parse_shifts("P"));
+    assert_eq!(None, parse_shifts("2 weeks ago"));
+}
+
+#[test]
+fn calendar_shifts_keep_wall_clock_across_dst() {
+    use chrono_tz::America::Los_Angeles;
+
+    let start = Los_Angeles.ymd(2019, 3, 9).and_hms(12, 0, 0);
+
+    let shifted =
+        apply_shifts(start, &[Shift::new(1, TimeUnit::Day)], &Calendar::default()).unwrap();
+    assert_eq!(Los_Angeles.ymd(2019, 3, 10).and_hms(12, 0, 0), shifted);
+
+    let shifted = apply_shifts(
+        start,
+        &[Shift::new(24, TimeUnit::Hour)],
+        &Calendar::default(),
+    )
+    .unwrap();
+    assert_eq!(Los_Angeles.ymd(2019, 3, 10).and_hms(13, 0, 0), shifted);
+    assert_eq!(
+        "2019-03-10T13:00:00-07:00",
+        shifted.with_timezone(&shifted.offset().fix()).to_rfc3339()
+    );
+
+    let shifted = apply_shifts(
+        start,
+        &[Shift::new(24, TimeUnit::Hour), Shift::new(1, TimeUnit::Day)],
+        &Calendar::default(),
+    )
+    .unwrap();
+    assert_eq!(Los_Angeles.ymd(2019, 3, 11).and_hms(13, 0, 0), shifted);
+}
+
+#[test]
+fn gaps_longer_than_an_hour_are_moved_past() {
+    use chrono_tz::Pacific::Apia;
+
+    // Samoa skipped 30 December 2011 when it moved across the date line.
+    let start = Apia.ymd(2011, 12, 29).and_hms(12, 0, 0);
+    let shifted =
+        apply_shifts(start, &[Shift::new(1, TimeUnit::Day)], &Calendar::default()).unwrap();
+    assert_eq!(Apia.ymd(2011, 12, 31).and_hms(12, 0, 0), shifted);
+    assert_eq!(
+        "2011-12-31T12:00:00+14:00",
+        shifted.with_timezone(&shifted.offset().fix()).to_rfc3339()
+    );
+
+    let local = NaiveDate::from_ymd(2011, 12, 30).and_hms(23, 30, 0);
+    assert_eq!(
+        Some(Apia.ymd(2011, 12, 31).and_hms(23, 30, 0)),
+        from_local(&Apia, &local)
+    );
+}
+
+#[test]
+fn month_arithmetic_clamps_to_month_length() {
+    assert_eq!(
+        Some(NaiveDate::from_ymd(2019, 2, 28)),
+        add_months(NaiveDate::from_ymd(2019, 1, 31), 1)
+    );
+    assert_eq!(
+        Some(NaiveDate::from_ymd(2020, 2, 29)),
+        add_months(NaiveDate::from_ymd(2019, 1, 31), 13)
+    );
+    assert_eq!(
+        Some(NaiveDate::from_ymd(2018, 11, 30)),
+        add_months(NaiveDate::from_ymd(2019, 3, 30), -4)
+    );
+}
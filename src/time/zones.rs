@@ -0,0 +1,128 @@
+use chrono::FixedOffset;
+use chrono_tz::Tz;
+
+const HOUR: i32 = 3600;
+
+/// Abbreviations people write instead of a zone name, with the offset each one
+/// stands for in each zone using it. The parser reads an abbreviation as the
+/// first offset, since `CEST` is +02:00 even in winter; the zones are there to
+/// look it up by.
+pub const ABBREVIATIONS: [(&str, &[(i32, Tz)]); 44] = [
+    (
+        "PST",
+        &[
+            (-8 * HOUR, Tz::PST8PDT),
+            (8 * HOUR, chrono_tz::Asia::Manila),
+        ],
+    ),
+    ("PDT", &[(-7 * HOUR, Tz::PST8PDT)]),
+    (
+        "MST",
+        &[
+            (-7 * HOUR, Tz::MST7MDT),
+            (-7 * HOUR, chrono_tz::America::Phoenix),
+        ],
+    ),
+    ("MDT", &[(-6 * HOUR, Tz::MST7MDT)]),
+    (
+        "CST",
+        &[
+            (-6 * HOUR, Tz::CST6CDT),
+            (8 * HOUR, chrono_tz::Asia::Shanghai),
+            (-5 * HOUR, chrono_tz::America::Havana),
+        ],
+    ),
+    (
+        "CDT",
+        &[
+            (-5 * HOUR, Tz::CST6CDT),
+            (-4 * HOUR, chrono_tz::America::Havana),
+        ],
+    ),
+    ("EST", &[(-5 * HOUR, Tz::EST5EDT)]),
+    ("EDT", &[(-4 * HOUR, Tz::EST5EDT)]),
+    ("AKST", &[(-9 * HOUR, chrono_tz::America::Anchorage)]),
+    ("AKDT", &[(-8 * HOUR, chrono_tz::America::Anchorage)]),
+    ("HST", &[(-10 * HOUR, chrono_tz::Pacific::Honolulu)]),
+    (
+        "AST",
+        &[
+            (-4 * HOUR, chrono_tz::America::Halifax),
+            (3 * HOUR, chrono_tz::Asia::Riyadh),
+        ],
+    ),
+    ("ADT", &[(-3 * HOUR, chrono_tz::America::Halifax)]),
+    (
+        "NST",
+        &[(-(3 * HOUR + HOUR / 2), chrono_tz::America::St_Johns)],
+    ),
+    (
+        "NDT",
+        &[(-(2 * HOUR + HOUR / 2), chrono_tz::America::St_Johns)],
+    ),
+    (
+        "BST",
+        &[
+            (HOUR, chrono_tz::Europe::London),
+            (6 * HOUR, chrono_tz::Asia::Dhaka),
+        ],
+    ),
+    (
+        "IST",
+        &[
+            (5 * HOUR + HOUR / 2, chrono_tz::Asia::Kolkata),
+            (HOUR, chrono_tz::Europe::Dublin),
+            (2 * HOUR, chrono_tz::Asia::Jerusalem),
+        ],
+    ),
+    ("IDT", &[(3 * HOUR, chrono_tz::Asia::Jerusalem)]),
+    ("WET", &[(0, Tz::WET)]),
+    ("WEST", &[(HOUR, Tz::WET)]),
+    ("CET", &[(HOUR, Tz::CET)]),
+    ("CEST", &[(2 * HOUR, Tz::CET)]),
+    ("EET", &[(2 * HOUR, Tz::EET)]),
+    ("EEST", &[(3 * HOUR, Tz::EET)]),
+    ("MSK", &[(3 * HOUR, chrono_tz::Europe::Moscow)]),
+    ("SAST", &[(2 * HOUR, chrono_tz::Africa::Johannesburg)]),
+    ("WAT", &[(HOUR, chrono_tz::Africa::Lagos)]),
+    ("CAT", &[(2 * HOUR, chrono_tz::Africa::Maputo)]),
+    ("EAT", &[(3 * HOUR, chrono_tz::Africa::Nairobi)]),
+    ("PKT", &[(5 * HOUR, chrono_tz::Asia::Karachi)]),
+    ("WIB", &[(7 * HOUR, chrono_tz::Asia::Jakarta)]),
+    ("SGT", &[(8 * HOUR, chrono_tz::Asia::Singapore)]),
+    ("HKT", &[(8 * HOUR, chrono_tz::Asia::Hong_Kong)]),
+    ("JST", &[(9 * HOUR, chrono_tz::Asia::Tokyo)]),
+    ("KST", &[(9 * HOUR, chrono_tz::Asia::Seoul)]),
+    ("AWST", &[(8 * HOUR, chrono_tz::Australia::Perth)]),
+    (
+        "ACST",
+        &[(9 * HOUR + HOUR / 2, chrono_tz::Australia::Adelaide)],
+    ),
+    (
+        "ACDT",
+        &[(10 * HOUR + HOUR / 2, chrono_tz::Australia::Adelaide)],
+    ),
+    ("AEST", &[(10 * HOUR, chrono_tz::Australia::Sydney)]),
+    ("AEDT", &[(11 * HOUR, chrono_tz::Australia::Sydney)]),
+    ("NZST", &[(12 * HOUR, chrono_tz::Pacific::Auckland)]),
+    ("NZDT", &[(13 * HOUR, chrono_tz::Pacific::Auckland)]),
+    ("GMT", &[(0, Tz::GMT)]),
+    ("UTC", &[(0, Tz::UTC)]),
+];
+
+/// Every offset and zone `abbreviation` could mean, if it's one we know.
+pub fn find_abbreviation(abbreviation: &str) -> Option<&'static [(i32, Tz)]> {
+    ABBREVIATIONS
+        .iter()
+        .find(|(name, _)| *name == abbreviation)
+        .map(|(_, zones)| *zones)
+}
+
+/// The offset `abbreviation` is read as, and whether it's also used for
+/// another offset somewhere else.
+pub fn abbreviation_offset(abbreviation: &str) -> Option<(FixedOffset, bool)> {
+    let meanings = find_abbreviation(abbreviation)?;
+    let (offset, _) = meanings[0];
+    let ambiguous = meanings.iter().any(|(x, _)| *x != offset);
+    Some((FixedOffset::east(offset), ambiguous))
+}